@@ -10,10 +10,84 @@ helped sign up. It also sends a little coin to the user for helping their friend
 mod geode_faucet {
 
     use ink::prelude::vec::Vec;
+    use ink::prelude::string::String;
     use ink::storage::Mapping;
+    use ink::env::hash::{Blake2x256, HashOutput};
+
+    // the most recipients root can pay in a single batch_payout call
+    const MAX_BATCH_RECIPIENTS: usize = 50;
+
+    // the length of the daily payout limit window in milliseconds
+    const DAY_MS: u64 = 86_400_000;
+
+    // the most accounts list_accounts will return in one page
+    const MAX_PAGE_SIZE: u32 = 100;
+
+    // the longest IP address accepted, long enough for an IPv6 address in text form
+    const MAX_IP_LEN: usize = 45;
+
+    // payout analytics are kept in this many hourly buckets (one day)
+    const PAYOUT_BUCKETS: u64 = 24;
+
+    // the length of one payout analytics bucket in milliseconds
+    const HOUR_MS: u64 = 3_600_000;
+
+    // the most accounts verify_accounts will check in one call, extra accounts are ignored
+    const MAX_VERIFY_ACCOUNTS: usize = 100;
+
+    // the number of recent claims kept in each account's claim history
+    const MAX_CLAIM_HISTORY: usize = 10;
+
+    // the longest memo accepted by get_coin_with_memo
+    const MAX_MEMO_LEN: usize = 32;
+
+    // the referral bonus can at most triple the payout (in basis points)
+    const MAX_REFERRAL_MULTIPLIER_BPS: u128 = 30_000;
+
+    // the longest faucet name and description set_metadata accepts
+    const MAX_NAME_LEN: usize = 64;
+    const MAX_DESCRIPTION_LEN: usize = 256;
+
+    // the most maintenance windows root can schedule at once
+    const MAX_MAINTENANCE_WINDOWS: usize = 10;
+
+    // the hard cap on accounts stored per IP address, whatever limit_ip_total
+    // is set to, so the list stays cheap to load and store on each claim
+    const MAX_IP_VEC_LEN: usize = 100;
+
+    // the most IP addresses eligibility_for_ips will check in one call
+    const MAX_ELIGIBILITY_IPS: usize = 10;
+
+    // the longest region code get_coin_with_region accepts
+    const MAX_REGION_LEN: usize = 8;
+
+    // the most extra faucet pools set_pool will create
+    const MAX_POOLS: usize = 10;
 
     // PRELIMINARY DATA STRUCTURES >>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>
 
+    // one entry in an account's claim history, (timestamp, payout)
+    pub type ClaimRecord = (u64, Balance);
+
+    // a (pool_id, hashed IP address) key for the per pool IP address mappings
+    pub type PoolIpKey = (u32, Vec<u8>);
+
+    // what is kept of a pruned account, (last claim timestamp, lifetime payout, claim nonce)
+    pub type PrunedRecord = (u64, Balance, u64);
+
+    // the error type of the standard PSP22 token contract, needed to decode
+    // the reply from a reward token transfer
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub enum PSP22Error {
+        Custom(String),
+        InsufficientBalance,
+        InsufficientAllowance,
+        ZeroRecipientAddress,
+        ZeroSenderAddress,
+        SafeTransferCheckFailed(String),
+    }
+
     #[derive(Clone, Debug, PartialEq, Eq)]
     #[ink::scale_derive(Encode, Decode, TypeInfo)]
     #[cfg_attr(feature = "std",derive(ink::storage::traits::StorageLayout,))]
@@ -22,6 +96,8 @@ mod geode_faucet {
         ip_address: Vec<u8>,
         pebble: AccountId,
         payout: Balance,
+        referrer: Option<AccountId>,
+        streak: u32,
     }
     
     impl Default for Pebble {
@@ -31,6 +107,8 @@ mod geode_faucet {
                 ip_address: <Vec<u8>>::default(),
                 pebble: AccountId::from([0x0; 32]),
                 payout: Balance::default(),
+                referrer: None,
+                streak: 0,
             }
         }
     }
@@ -41,24 +119,349 @@ mod geode_faucet {
     pub struct ViewStats { 
         eligible_payout: Balance,
         get_payout: Balance,
+        first_claim_payout: Balance,
         limit_timer: u64,
         limit_ip_total: u128,
         total_pebble_accounts: u128,
         total_payouts: Balance,
+        paused: bool,
+        lifetime_cap: Balance,
+        referrer_payout: Balance,
+        total_unique_ips: u128,
+        daily_limit: Balance,
+        day_spent: Balance,
+        allowlist_only: bool,
+        remaining_claims: Balance,
+        ip_limit_timer: u64,
+        reserve: Balance,
+        // only batch_payout failures are counted here: recipients it skipped because
+        // the transfer failed or the balance could no longer cover them. A failed
+        // get_coin transfer reverts the claim with Error::PayoutFailed, so it can
+        // never be counted
+        total_failed_payouts: u128,
+        token_decimals: u8,
+        max_accounts: u128,
+        max_claims_per_block: u32,
+        decay_enabled: bool,
+        initial_funded: Balance,
+        events_enabled: bool,
+        max_ips_per_account: u128,
+        new_account_bypass_timer: bool,
+        restrict_verify: bool,
+        reward_token: Option<AccountId>,
+        token_amount: Balance,
+        token_strict: bool,
+        total_failed_token_transfers: u128,
+        storage_version: u16,
+        min_account_age_ms: u64,
+        referral_multiplier_bps: u16,
+        max_payout_cap: Balance,
+        one_account_per_ip: bool,
+        min_transfer: Balance,
+        min_transfer_bump: bool,
+        streak_bonus: Balance,
+        open_time: u64,
+        close_time: u64,
+        total_funded: Balance,
+        pending_total: Balance,
+        pending_timeout_ms: u64,
+        round_to: Balance,
+        claim_fee: Balance,
+        total_fees_collected: Balance,
+        stats_overflowed: bool,
+        auto_pause_threshold: Balance,
+    }
+
+
+    #[derive(Clone, Debug, PartialEq, Eq, Default)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std",derive(ink::storage::traits::StorageLayout,))]
+    pub struct DashboardView { 
+        // includes the paused flag and remaining_claims
+        stats: ViewStats,
+        contract_balance: Balance,
+    }
+
+
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub enum EligibilityStatus {
+        // the caller can get coin right now
+        Eligible,
+        // the caller must wait, time is in the same units as limit_timer
+        RateLimited { seconds_remaining: u64 },
+        // the IP address already has the limit of total IP tags
+        IpLimitReached,
+        // the caller or their IP address is on a blocklist
+        Blocked,
+        // the contract does not hold enough coin for a payout above its reserve
+        ContractDrained,
+        // the faucet is paused by the root account
+        Paused,
+        // the caller has already received their lifetime cap of coin
+        LifetimeCapReached,
+        // the faucet has paid out its daily limit
+        DailyLimitReached,
+        // the IP address is empty or longer than MAX_IP_LEN
+        InvalidIpAddress,
+        // the faucet is allowlist only and the caller is not on the allowlist
+        NotAllowlisted,
+        // the faucet has reached its maximum number of accounts
+        MaxAccountsReached,
+        // the faucet has paid out its claims for this block
+        BlockRateLimited,
+        // the caller has already used their limit of distinct IP addresses
+        TooManyIps,
+        // the account was first seen less than min_account_age_ms ago
+        AccountTooNew,
+        // one_account_per_ip is on and another account already claimed from this IP address
+        IpAlreadyUsed,
+        // the payout is below min_transfer and min_transfer_bump is off
+        PayoutBelowMinimum,
+        // the claim window has not opened yet or has already closed
+        FaucetClosed,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub struct UserStatus { 
+        // everything a front end needs to render the claim screen for one account
+        verified: bool,
+        blocked: bool,
+        eligibility: EligibilityStatus,
+        seconds_remaining: u64,
+        last_claim: u64,
+        preview_payout: Balance,
+    }
+
+    // the settings and accounting for one extra faucet pool, pool 0 is the
+    // main faucet and uses the regular settings
+    // extra pools are claimed and configured through their own messages
+    // (set_pool, get_coin_from_pool) rather than a pool_id on get_coin and every
+    // setter, so the existing messages keep their ABI
+    #[derive(Clone, Debug, PartialEq, Eq, Default)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std",derive(ink::storage::traits::StorageLayout,))]
+    pub struct PoolSettings { 
+        get_payout: Balance,
+        limit_timer: u64,
+        // 0 means no budget limit
+        budget: Balance,
+        paid_out: Balance,
+        claims: u128,
+    }
+
+    // a claim queued by queue_claim, with what is needed to finalize it or
+    // to take it back out of the stats when it expires
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std",derive(ink::storage::traits::StorageLayout,))]
+    pub struct PendingClaim { 
+        amount: Balance,
+        fee: Balance,
+        expires: u64,
+        queued_at: u64,
+        ip_key: Vec<u8>,
+    }
+
+    // ABI NOTE: since v0.2.0 get_coin returns a ClaimReceipt rather than just the
+    // amount, so wallets can show the next claim time straight away.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub struct ClaimReceipt { 
+        // the coin sent and when the account can next claim (block timestamp in ms)
+        amount: Balance,
+        next_eligible: u64,
+    }
+
+
+    // everything check_claim_allowed works out for a claim that would succeed,
+    // so get_coin can act on it without reading storage again
+    struct ClaimPlan {
+        now: u64,
+        ip_key: Vec<u8>,
+        user_details: Pebble,
+        ip_tags: Vec<AccountId>,
+        payout: Balance,
+        referrer: Option<AccountId>,
+        required: Balance,
+        day_start: u64,
+        day_spent: Balance,
+        block: BlockNumber,
+        claims_this_block: u32,
+        streak: u32,
+        fee: Balance,
     }
 
 
     // EVENT DEFINITIONS >>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>
 
+    // ABI NOTE: since v0.2.0 the payout amount is a topic on PayoutEvent and ClaimEvent
+    // so indexers can filter by payout size. The timestamp is no longer a topic 
+    // to stay within the limit of 4 topics (including the event signature).
+
     #[ink(event)]
     // writes a new payout to the chain. 
     pub struct PayoutEvent {
+        timestamp: u64,
+        #[ink(topic)]
+        user_ip: Vec<u8>,
+        #[ink(topic)]
+        pebble: AccountId,
+        #[ink(topic)]
+        payout: Balance,
+    }
+
+    #[ink(event)]
+    // writes a new claim made through get_coin to the chain. 
+    pub struct ClaimEvent {
+        timestamp: u64,
+        #[ink(topic)]
+        user_ip: Vec<u8>,
+        #[ink(topic)]
+        pebble: AccountId,
+        #[ink(topic)]
+        payout: Balance,
+    }
+
+    #[ink(event)]
+    // writes a root withdrawal of funds to the chain. 
+    pub struct WithdrawEvent {
+        #[ink(topic)]
+        timestamp: u64,
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    // writes a change of the root account to the chain. 
+    pub struct RootChangedEvent {
+        #[ink(topic)]
+        old_root: AccountId,
+        #[ink(topic)]
+        new_root: AccountId,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    // writes the final balance sent out when the faucet is terminated. 
+    pub struct TerminatedEvent {
+        #[ink(topic)]
+        beneficiary: AccountId,
+        timestamp: u64,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    // writes coin sent in to fund the faucet to the chain. 
+    pub struct FundedEvent {
+        #[ink(topic)]
+        funder: AccountId,
+        amount: Balance,
+        new_balance: Balance,
+    }
+
+    #[ink(event)]
+    // writes new payouts and limits set through set_payouts_and_fund to the chain. 
+    pub struct SettingsChangedEvent {
+        eligible_payout: Balance,
+        get_payout: Balance,
+        first_claim_payout: Balance,
+        limit_timer: u64,
+        limit_ip_total: u128,
+        lifetime_cap: Balance,
+        daily_limit: Balance,
+    }
+
+    #[ink(event)]
+    // writes the memo attached to a claim made through get_coin_with_memo to the chain. 
+    pub struct ClaimMemoEvent {
+        timestamp: u64,
+        #[ink(topic)]
+        pebble: AccountId,
+        #[ink(topic)]
+        memo: Vec<u8>,
+        payout: Balance,
+    }
+
+    #[ink(event)]
+    // writes a reset of the aggregate stats to the chain, with the payout total that was
+    // cleared and the number of accounts at the time (which is not cleared). 
+    pub struct StatsResetEvent {
+        #[ink(topic)]
+        root: AccountId,
+        timestamp: u64,
+        total_payouts: Balance,
+        total_pebble_accounts: u128,
+    }
+
+    #[ink(event)]
+    // writes an admin adding an account to the blocklist to the chain. 
+    pub struct AccountBlockedEvent {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        admin: AccountId,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    // writes an admin removing an account from the blocklist to the chain. 
+    pub struct AccountUnblockedEvent {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        admin: AccountId,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    // writes an admin adding a (hashed) IP address to the blocklist to the chain. 
+    pub struct IpBlockedEvent {
+        #[ink(topic)]
+        user_ip: Vec<u8>,
         #[ink(topic)]
+        admin: AccountId,
         timestamp: u64,
+    }
+
+    #[ink(event)]
+    // writes an admin removing a (hashed) IP address from the blocklist to the chain. 
+    pub struct IpUnblockedEvent {
         #[ink(topic)]
         user_ip: Vec<u8>,
         #[ink(topic)]
+        admin: AccountId,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    // writes a reconcile of total_funded against the live balance to the chain. 
+    pub struct ReconcileEvent {
+        #[ink(topic)]
+        root: AccountId,
+        timestamp: u64,
+        adjustment: Balance,
+        total_funded: Balance,
+    }
+
+    #[ink(event)]
+    // writes the faucet pausing itself when its balance ran low to the chain. 
+    pub struct AutoPausedEvent {
+        timestamp: u64,
+        balance: Balance,
+        threshold: Balance,
+    }
+
+    #[ink(event)]
+    // writes a new claim made from an extra faucet pool to the chain. 
+    pub struct PoolClaimEvent {
+        #[ink(topic)]
+        pool_id: u32,
+        #[ink(topic)]
         pebble: AccountId,
+        timestamp: u64,
         payout: Balance,
     }
 
@@ -73,22 +476,187 @@ mod geode_faucet {
         PermissionDenied,
         // pauout failed to go through
         PayoutFailed,
+        // the contract does not hold enough coin for the request
+        InsufficientBalance,
+        // the faucet is paused by the root account
+        Paused,
+        // the account has already received its lifetime cap of coin
+        LifetimeCapReached,
+        // the account has been blocked by the root account
+        AccountBlocked,
+        // the IP address has been blocked by the root account
+        IpBlocked,
+        // too many recipients were given for a single batch
+        TooManyRecipients,
+        // an account cannot refer itself
+        SelfReferral,
+        // the faucet has paid out its daily limit
+        DailyLimitReached,
+        // the IP address is empty or longer than MAX_IP_LEN
+        InvalidIpAddress,
+        // the faucet is allowlist only and the account is not on the allowlist
+        NotAllowlisted,
+        // the IP address has claimed too recently
+        IpRateLimited,
+        // the contract balance cannot cover the payout and the reserve
+        ContractDrained,
+        // the IP address already has the limit of total IP tags
+        IpLimitReached,
+        // the account has claimed too recently
+        RateLimited,
+        // the faucet has reached its maximum number of accounts
+        MaxAccountsReached,
+        // the faucet has paid out its claims for this block
+        BlockRateLimited,
+        // the account has already used its limit of distinct IP addresses
+        TooManyIps,
+        // the nonce is not the next claim nonce for the beneficiary
+        BadNonce,
+        // the signature was not made by the beneficiary
+        BadSignature,
+        // the reward token transfer failed while token_strict is on
+        TokenTransferFailed,
+        // the memo is longer than MAX_MEMO_LEN bytes
+        MemoTooLong,
+        // the account was first seen less than min_account_age_ms ago
+        AccountTooNew,
+        // a payout setting is above the max_payout_cap
+        PayoutTooHigh,
+        // the faucet name or description is too long
+        MetadataTooLong,
+        // one_account_per_ip is on and another account already claimed from this IP address
+        IpAlreadyUsed,
+        // the payout is below min_transfer and min_transfer_bump is off
+        PayoutBelowMinimum,
+        // the zero address cannot be made root
+        InvalidRoot,
+        // coin cannot be sent to the zero address
+        InvalidRecipient,
+        // the claim window has not opened yet or has already closed
+        FaucetClosed,
+        // there are already MAX_MAINTENANCE_WINDOWS maintenance windows
+        TooManyWindows,
+        // the beneficiary already has a pending claim
+        ClaimPending,
+        // the beneficiary has no pending claim
+        NoPendingClaim,
+        // the pending claim has expired and can only be expired
+        ClaimExpired,
+        // the pending claim has not expired yet
+        ClaimNotExpired,
+        // the claim fee would take the whole of a payout
+        FeeTooHigh,
+        // the region code is longer than MAX_REGION_LEN bytes
+        RegionTooLong,
+        // the pool does not exist, or pool 0 was used where an extra pool is needed
+        InvalidPool,
+        // the pool has paid out its whole budget
+        PoolBudgetReached,
+        // there are already MAX_POOLS extra faucet pools
+        TooManyPools,
+        // the beneficiary has not registered a signing key with register_signer
+        SignerNotRegistered,
+        // there are pending claims that must be finalized or expired first
+        ClaimsPending,
     }
 
 
     // ACTUAL CONTRACT STORAGE >>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>
+
+    // MIGRATION NOTE: since v0.2.0 every IP keyed mapping (ipaddress_count, blocked_ips,
+    // ip_last_claim) and the ip_address in each Pebble hold the Blake2x256 hash of the 
+    // IP address rather than the raw bytes. Entries written by earlier versions are keyed
+    // by raw IP addresses and will not match, so IP limits effectively start fresh.
+    // Pebble also gained the referrer and streak fields, so Pebble entries written
+    // by earlier versions will not decode and need migrating.
+    // The rootset flag was dropped, root is set in the constructor and is always set.
     #[ink(storage)]
     pub struct ContractStorage {
         user_map: Mapping<AccountId, Pebble>,
         ipaddress_count: Mapping<Vec<u8>, Vec<AccountId>>,
+        blocklist: Mapping<AccountId, bool>,
+        blocked_ips: Mapping<Vec<u8>, bool>,
         root: AccountId,
-        rootset: u8,
+        pending_root: Option<AccountId>,
         eligible_payout: Balance,
         get_payout: Balance,
+        first_claim_payout: Balance,
         limit_timer: u64,
         limit_ip_total: u128,
         total_pebble_accounts: u128, 
         total_payouts: Balance,
+        paused: bool,
+        lifetime_cap: Balance,
+        referrer_payout: Balance,
+        referral_counts: Mapping<AccountId, u128>,
+        total_unique_ips: u128,
+        daily_limit: Balance,
+        day_start: u64,
+        day_spent: Balance,
+        accounts: Mapping<u32, AccountId>,
+        account_index: Mapping<AccountId, u32>,
+        account_count: u32,
+        allowlist_only: bool,
+        allowlist: Mapping<AccountId, bool>,
+        ip_limit_timer: u64,
+        ip_last_claim: Mapping<Vec<u8>, u64>,
+        reserve: Balance,
+        total_failed_payouts: u128,
+        token_decimals: u8,
+        max_accounts: u128,
+        max_claims_per_block: u32,
+        last_block: BlockNumber,
+        claims_this_block: u32,
+        admins: Mapping<AccountId, bool>,
+        payout_buckets: Vec<(u64, Balance)>,
+        decay_enabled: bool,
+        initial_funded: Balance,
+        events_enabled: bool,
+        claim_nonce: Mapping<AccountId, u64>,
+        claim_history: Mapping<AccountId, Vec<ClaimRecord>>,
+        max_ips_per_account: u128,
+        ips_seen: Mapping<AccountId, Vec<Vec<u8>>>,
+        new_account_bypass_timer: bool,
+        restrict_verify: bool,
+        verifier_allowlist: Mapping<AccountId, bool>,
+        reward_token: Option<AccountId>,
+        token_amount: Balance,
+        token_strict: bool,
+        total_failed_token_transfers: u128,
+        storage_version: u16,
+        first_seen: Mapping<AccountId, u64>,
+        min_account_age_ms: u64,
+        referral_multiplier_bps: u16,
+        max_payout_cap: Balance,
+        name: Vec<u8>,
+        description: Vec<u8>,
+        one_account_per_ip: bool,
+        bypass_code_hash: Option<Hash>,
+        min_transfer: Balance,
+        min_transfer_bump: bool,
+        streak_bonus: Balance,
+        open_time: u64,
+        close_time: u64,
+        total_funded: Balance,
+        ip_total_payout: Mapping<Vec<u8>, Balance>,
+        maintenance_windows: Vec<(u64, u64)>,
+        pending_claims: Mapping<AccountId, PendingClaim>,
+        pending_total: Balance,
+        pending_timeout_ms: u64,
+        round_to: Balance,
+        claim_fee: Balance,
+        total_fees_collected: Balance,
+        region_totals: Mapping<Vec<u8>, Balance>,
+        stats_overflowed: bool,
+        auto_pause_threshold: Balance,
+        pools: Mapping<u32, PoolSettings>,
+        pruned_accounts: Mapping<AccountId, PrunedRecord>,
+        pool_users: Mapping<(u32, AccountId), Pebble>,
+        pool_ip_accounts: Mapping<PoolIpKey, Vec<AccountId>>,
+        pool_ip_last_claim: Mapping<PoolIpKey, u64>,
+        pool_ids: Vec<u32>,
+        signer_keys: Mapping<AccountId, [u8; 33]>,
+        bypass_codes_used: Mapping<AccountId, Hash>,
     }
 
     // CONTRACT LOGIC >>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>
@@ -96,22 +664,105 @@ mod geode_faucet {
     impl ContractStorage {
         
         // CONSTRUCTORS >>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>
-        // Constructors are implicitly payable when the contract is instantiated.
+        // The constructor is payable so the faucet can be funded when it is instantiated.
 
-        #[ink(constructor)]
-        pub fn new() -> Self {
-            Self {
+        // the deployer names the initial root account so root cannot be front-run
+        #[ink(constructor, payable)]
+        pub fn new(initial_root: AccountId) -> Result<Self, Error> {
+            // a zero address root would leave the faucet with no governance
+            if Self::is_zero_address(initial_root) {
+                return Err(Error::InvalidRoot)
+            }
+            let mut contract = Self {
                 user_map: Mapping::default(),
                 ipaddress_count: Mapping::default(),
-                root: AccountId::from([0x0; 32]),
-                rootset: 0,
+                blocklist: Mapping::default(),
+                blocked_ips: Mapping::default(),
+                root: initial_root,
+                pending_root: None,
                 eligible_payout: 0,
                 get_payout: 0,
+                first_claim_payout: 0,
                 limit_timer: u64::default(),
                 limit_ip_total: u128::default(),
                 total_pebble_accounts: u128::default(), 
                 total_payouts: Balance::default(),
-            }
+                paused: false,
+                lifetime_cap: Balance::default(),
+                referrer_payout: Balance::default(),
+                referral_counts: Mapping::default(),
+                total_unique_ips: u128::default(),
+                daily_limit: Balance::default(),
+                day_start: u64::default(),
+                day_spent: Balance::default(),
+                accounts: Mapping::default(),
+                account_index: Mapping::default(),
+                account_count: u32::default(),
+                allowlist_only: false,
+                allowlist: Mapping::default(),
+                ip_limit_timer: u64::default(),
+                ip_last_claim: Mapping::default(),
+                reserve: Balance::default(),
+                total_failed_payouts: u128::default(),
+                token_decimals: u8::default(),
+                max_accounts: u128::default(),
+                max_claims_per_block: u32::default(),
+                last_block: BlockNumber::default(),
+                claims_this_block: u32::default(),
+                admins: Mapping::default(),
+                payout_buckets: Vec::new(),
+                decay_enabled: false,
+                initial_funded: Balance::default(),
+                events_enabled: true,
+                claim_nonce: Mapping::default(),
+                claim_history: Mapping::default(),
+                max_ips_per_account: u128::default(),
+                ips_seen: Mapping::default(),
+                new_account_bypass_timer: true,
+                restrict_verify: false,
+                verifier_allowlist: Mapping::default(),
+                reward_token: None,
+                token_amount: Balance::default(),
+                token_strict: false,
+                total_failed_token_transfers: u128::default(),
+                storage_version: 1,
+                first_seen: Mapping::default(),
+                min_account_age_ms: 0,
+                referral_multiplier_bps: 0,
+                max_payout_cap: Balance::default(),
+                name: Vec::default(),
+                description: Vec::default(),
+                one_account_per_ip: false,
+                bypass_code_hash: None,
+                min_transfer: Balance::default(),
+                min_transfer_bump: false,
+                streak_bonus: Balance::default(),
+                open_time: 0,
+                close_time: 0,
+                total_funded: Balance::default(),
+                ip_total_payout: Mapping::default(),
+                maintenance_windows: Vec::default(),
+                pending_claims: Mapping::default(),
+                pending_total: Balance::default(),
+                pending_timeout_ms: HOUR_MS,
+                round_to: Balance::default(),
+                claim_fee: Balance::default(),
+                total_fees_collected: Balance::default(),
+                region_totals: Mapping::default(),
+                stats_overflowed: false,
+                auto_pause_threshold: Balance::default(),
+                pools: Mapping::default(),
+                pruned_accounts: Mapping::default(),
+                pool_users: Mapping::default(),
+                pool_ip_accounts: Mapping::default(),
+                pool_ip_last_claim: Mapping::default(),
+                pool_ids: Vec::default(),
+                signer_keys: Mapping::default(),
+                bypass_codes_used: Mapping::default(),
+            };
+            // log any coin sent in with the deployment
+            contract.record_funding();
+            Ok(contract)
         }
 
 
@@ -119,47 +770,92 @@ mod geode_faucet {
         // MESSGE FUNCTIONS THAT ALTER CONTRACT STORAGE >>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>
         // >>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>
         
-        // 0 🟢 SET ROOT ACCOUNT
-        // This message lets us set the root account 
+        // 0 🟢 SET ROOT ACCOUNT (ROOT ONLY)
+        // This message lets the current root account set a new root account 
         #[ink(message)]
         pub fn set_root_account(&mut self, 
             new_root: AccountId,
         ) -> Result<(), Error> {
             let caller = Self::env().caller();
-            // check that the root user is not yet set
-            if self.rootset != 1 || self.root == caller {
+            // a zero address root would lock governance for good
+            if Self::is_zero_address(new_root) {
+                return Err(Error::InvalidRoot)
+            }
+            // check that the caller is the root user
+            if self.root == caller {
                 // proceed to set up the root user
+                let old_root = self.root;
                 self.root = new_root;
-                self.rootset = 1;
+
+                // emit an event only if the root actually changed
+                if old_root != new_root {
+                    // a nomination made by the old root no longer stands
+                    self.pending_root = None;
+                    Self::env().emit_event(RootChangedEvent {
+                        old_root,
+                        new_root,
+                        timestamp: self.env().block_timestamp(),
+                    });
+                }
             }
             else {
-                // if the root user has already been set 
-                // and the caller is not that root user, send an error
+                // if the caller is not the root user, send an error
                 return Err(Error::PermissionDenied)
             }
             Ok(())
         }
 
 
-        // 1 🟢 SET PAYOUTS AND LIMITS & SEND COIN (ROOT ONLY)
-        // This message lets the root account set/update payouts and limits and send coin
+        // 1 🟢 SET PAYOUTS AND LIMITS & SEND COIN (ADMIN ONLY)
+        // This message lets an admin set/update payouts and limits and send coin
         #[ink(message, payable)]
+        #[allow(clippy::too_many_arguments)]
         pub fn set_payouts_and_fund(&mut self, 
             new_eligible_payout: Balance,
             new_get_payout: Balance,
+            new_first_claim_payout: Balance,
             new_limit_timer: u64,
-            new_limit_ip_total: u128
+            new_limit_ip_total: u128,
+            new_lifetime_cap: Balance,
+            new_daily_limit: Balance
         ) -> Result<(), Error> {
-            // check that the caller is the root user
+            // check that the caller is an admin
             let caller = Self::env().caller();
-            if self.root == caller {
+            if self.is_admin(caller) {
+                // guard against fat-fingered payouts that could drain the faucet
+                if !self.within_payout_cap(new_eligible_payout)
+                || !self.within_payout_cap(new_get_payout)
+                || !self.within_payout_cap(new_first_claim_payout) {
+                    return Err(Error::PayoutTooHigh)
+                }
+                // the claim fee must leave something of each payout
+                if !Self::fee_allowed(self.claim_fee, new_get_payout)
+                || (new_first_claim_payout > 0 && !Self::fee_allowed(self.claim_fee, new_first_claim_payout)) {
+                    return Err(Error::FeeTooHigh)
+                }
+
                 // set all the things
                 self.eligible_payout = new_eligible_payout;
                 self.get_payout = new_get_payout;
+                self.first_claim_payout = new_first_claim_payout;
                 self.limit_timer = new_limit_timer;
                 self.limit_ip_total = new_limit_ip_total;
+                self.lifetime_cap = new_lifetime_cap;
+                self.daily_limit = new_daily_limit;
+
+                // when coin is sent in, the new balance becomes the starting
+                // point for the payout decay curve
+                if self.env().transferred_value() > 0 {
+                    self.initial_funded = self.env().balance();
+                }
+                self.record_funding();
+
+                // emit an event so integrators can refresh cached settings
+                self.emit_settings_changed();
             }
-            // if the caller is not the root user, return fail
+            // if the caller is not an admin, return fail
+            // returning an error reverts the whole call, including any coin
+            // that was sent with it, so a non-admin caller keeps their coin
             else {
                 // error
                 return Err(Error::PermissionDenied)
@@ -170,125 +866,31 @@ mod geode_faucet {
 
         // 2 🟢 CHECK ELIGIBILITY [ANYONE]
         // lets any one user check if they are eligible to get coin
-        // if eligible, it transfers the eligible_payout to their account
+        // this is read only, no coin is transferred and no event is emitted
+        // ABI NOTE: since v0.2.0 this returns Ok(true) if eligible and Ok(false) if not,
+        // rather than a (ClaimResult, limit_timer, limit_ip_total) tuple. Err is kept for
+        // genuine failures (Error::PayoutFailed), which this read only check never hits.
+        // Use limit_timer() and limit_ip_total() for the settings.
         #[ink(message)]
-        pub fn check_eligibility(&self, my_ip_address: Vec<u8>) -> (u8, u64, u128) {
-            let caller = Self::env().caller();
-            let mut result: u8 = 0;
-
-            let now = self.env().block_timestamp();
-            let user_details = self.user_map.get(caller).unwrap_or_default();
-            let time_since = now.wrapping_sub(user_details.timestamp);
-            let ip_tags = self.ipaddress_count.get(my_ip_address.clone()).unwrap_or_default();
-            let ip_tags_len: u128 = ip_tags.len().try_into().unwrap_or_default();
-
-            // return YES if...
-            // the IP address has < the limit of total IP tags AND
-            // EITHER the user has not paid out before OR...
-            // the user has paid out before but it has been long enough
-            if (ip_tags_len < self.limit_ip_total || ip_tags.contains(&caller)) 
-            && (time_since >= self.limit_timer || user_details.payout == 0) {
-                // change results to yes
-                result = 1;
-
-                // payout the eligible_payout to the caller
-                // make sure the contract has enough balance
-                if self.env().balance() > self.eligible_payout {
-                    if self.env().transfer(caller, self.eligible_payout).is_err() {
-                        result = 2;
-                    }
-                }
-                
-                // emit and event for the payout
-                Self::env().emit_event(PayoutEvent {
-                    timestamp: now,
-                    user_ip: my_ip_address,
-                    pebble: caller,
-                    payout: self.eligible_payout,
-                });
-            }
-
-            // return result (yes or no)
-            (result, self.limit_timer, self.limit_ip_total)
+        pub fn check_eligibility(&self, my_ip_address: Vec<u8>) -> Result<bool, Error> {
+            Ok(self.is_eligible(my_ip_address))
         }
 
 
         // 3 🟢 GET COIN [ANYONE]
         // lets any one user who is eligible, get coin from the faucet
+        // returns a receipt with the amount transferred to the caller and
+        // when they can next claim
         #[ink(message)]
         pub fn get_coin(&mut self, 
             my_ip_address: Vec<u8>
-        ) -> Result<(), Error> {
+        ) -> Result<ClaimReceipt, Error> {
             let caller = Self::env().caller();
-            let now = self.env().block_timestamp();
-
-            let mut newuser: u8 = 1;
-            if self.user_map.contains(&caller) {
-                newuser = 0;
-            }
-
-            let mut user_details = self.user_map.get(caller).unwrap_or_default();
-            let time_since = now.wrapping_sub(user_details.timestamp);
-            let mut ip_tags = self.ipaddress_count.get(my_ip_address.clone()).unwrap_or_default();
-            let ip_tags_len: u128 = ip_tags.len().try_into().unwrap_or_default();
-
-            // account is eligible if...
-            // the IP address has < the limit of total IP tags AND
-            // EITHER the user has not paid out before OR...
-            // the user has paid out before but it has been long enough
-            if (ip_tags_len < self.limit_ip_total || ip_tags.contains(&caller)) 
-            && (time_since >= self.limit_timer || user_details.payout < 1) {
-
-                // payout the get_payout to the caller
-                // make sure the contract has enough balance
-                if self.env().balance() > self.get_payout {
-                    if self.env().transfer(caller, self.get_payout).is_err() {
-                        return Err(Error::PayoutFailed);
-                    }
-                }
-                
-                // update the user details (timestamp updated on get coin only)
-                user_details.payout = user_details.payout.saturating_add(self.get_payout);
-                user_details.ip_address = my_ip_address.clone();
-                user_details.pebble = caller;
-                user_details.timestamp = now;
-                
-                // update the user_map
-                self.user_map.insert(caller, &user_details);
-
-                // update the ip address count 
-                if ip_tags.contains(&caller) {
-                    // do nothing
-                }
-                else {
-                    // add the caller and update the map
-                    ip_tags.push(caller);
-                    self.ipaddress_count.insert(my_ip_address.clone(), &ip_tags);
-                }
-
-                // update total total_payouts
-                self.total_payouts = self.total_payouts.saturating_add(self.get_payout);
-
-                // update the total_pebble_accounts IF this is a new account
-                if newuser == 1 {
-                    self.total_pebble_accounts = self.total_pebble_accounts.saturating_add(1);
-                }
-                
-                // emit event for the payout
-                Self::env().emit_event(PayoutEvent {
-                    timestamp: now,
-                    user_ip: my_ip_address,
-                    pebble: caller,
-                    payout: self.get_payout,
-                });
-
-            }
-            else {
-                // send error that permission is denied
-                return Err(Error::PermissionDenied);
-            }
-
-            Ok(())
+            let amount = self.pay_claim(caller, my_ip_address, None, false, false, 0)?;
+            Ok(ClaimReceipt {
+                amount,
+                next_eligible: self.env().block_timestamp().saturating_add(self.limit_timer),
+            })
         }
 
 
@@ -299,10 +901,57 @@ mod geode_faucet {
             let stats = ViewStats {
                 eligible_payout: self.eligible_payout,
                 get_payout: self.get_payout,
+                first_claim_payout: self.first_claim_payout,
                 limit_timer: self.limit_timer,
                 limit_ip_total: self.limit_ip_total,
                 total_pebble_accounts: self.total_pebble_accounts,
                 total_payouts: self.total_payouts,
+                paused: self.paused,
+                lifetime_cap: self.lifetime_cap,
+                referrer_payout: self.referrer_payout,
+                total_unique_ips: self.total_unique_ips,
+                daily_limit: self.daily_limit,
+                day_spent: self.day_spent,
+                allowlist_only: self.allowlist_only,
+                // estimate how many more claims the balance above the reserve
+                // and pending claims can cover
+                remaining_claims: self.env().balance().saturating_sub(self.reserve)
+                    .saturating_sub(self.pending_total)
+                    .checked_div(self.get_payout).unwrap_or_default(),
+                ip_limit_timer: self.ip_limit_timer,
+                reserve: self.reserve,
+                total_failed_payouts: self.total_failed_payouts,
+                token_decimals: self.token_decimals,
+                max_accounts: self.max_accounts,
+                max_claims_per_block: self.max_claims_per_block,
+                decay_enabled: self.decay_enabled,
+                initial_funded: self.initial_funded,
+                events_enabled: self.events_enabled,
+                max_ips_per_account: self.max_ips_per_account,
+                new_account_bypass_timer: self.new_account_bypass_timer,
+                restrict_verify: self.restrict_verify,
+                reward_token: self.reward_token,
+                token_amount: self.token_amount,
+                token_strict: self.token_strict,
+                total_failed_token_transfers: self.total_failed_token_transfers,
+                storage_version: self.storage_version,
+                min_account_age_ms: self.min_account_age_ms,
+                referral_multiplier_bps: self.referral_multiplier_bps,
+                max_payout_cap: self.max_payout_cap,
+                one_account_per_ip: self.one_account_per_ip,
+                min_transfer: self.min_transfer,
+                min_transfer_bump: self.min_transfer_bump,
+                streak_bonus: self.streak_bonus,
+                open_time: self.open_time,
+                close_time: self.close_time,
+                total_funded: self.total_funded,
+                pending_total: self.pending_total,
+                pending_timeout_ms: self.pending_timeout_ms,
+                round_to: self.round_to,
+                claim_fee: self.claim_fee,
+                total_fees_collected: self.total_fees_collected,
+                stats_overflowed: self.stats_overflowed,
+                auto_pause_threshold: self.auto_pause_threshold,
             };
             // return results
             stats
@@ -310,21 +959,4790 @@ mod geode_faucet {
 
 
         // 5 🟢 VERIFY ACCOUNT 
-        // for use in other apps, returns 1 if the account has tagged the faucet at least once
+        // for use in other apps, returns true if the account has tagged the faucet at least once
+        // (since v0.2.0 this returns a bool rather than a u8 of 0 or 1)
+        #[ink(message)]
+        pub fn verify_account(&self, verify: AccountId) -> bool {
+            self.verify_allowed() && self.has_claimed(verify)
+        }
+
+
+        // 6 🟢 IS ELIGIBLE [ANYONE]
+        // read only check that returns true if the caller could get coin right now
+        #[ink(message)]
+        pub fn is_eligible(&self, my_ip_address: Vec<u8>) -> bool {
+            self.eligibility_reason(my_ip_address) == EligibilityStatus::Eligible
+        }
+
+
+        // 7 🟢 WITHDRAW FUNDS (ROOT ONLY)
+        // lets the root account reclaim unused coin from the faucet
+        #[ink(message)]
+        pub fn withdraw_funds(&mut self, 
+            amount: Balance,
+            to: AccountId
+        ) -> Result<(), Error> {
+            // check that the caller is the root user
+            let caller = Self::env().caller();
+            if self.root != caller {
+                return Err(Error::PermissionDenied)
+            }
+            if Self::is_zero_address(to) {
+                return Err(Error::InvalidRecipient)
+            }
+
+            // make sure the contract has enough balance, coin held for
+            // pending claims cannot be withdrawn
+            if self.env().balance().saturating_sub(self.pending_total) < amount {
+                return Err(Error::InsufficientBalance)
+            }
+
+            // send the coin
+            if self.env().transfer(to, amount).is_err() {
+                return Err(Error::PayoutFailed)
+            }
+
+            // emit an event for the withdrawal
+            Self::env().emit_event(WithdrawEvent {
+                timestamp: self.env().block_timestamp(),
+                to,
+                amount,
+            });
+
+            Ok(())
+        }
+
+
+        // 8 🟢 PAUSE OR UNPAUSE THE FAUCET (ADMIN ONLY)
+        // lets an admin halt or resume all payouts
         #[ink(message)]
-        pub fn verify_account(&self, verify: AccountId) -> u8 {
-            let mut result: u8 = 0;
-            if self.user_map.contains(&verify) {
-                result = 1;
+        pub fn set_paused(&mut self, paused: bool) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
             }
-            //return result
-            result
+            self.paused = paused;
+            Ok(())
         }
 
 
-        // END OF MESSAGE FUNCTIONS
+        // 9 🟢 TRANSFER ROOT (ROOT ONLY)
+        // first step of a root handoff, nominates the account that may accept root
+        #[ink(message)]
+        pub fn transfer_root(&mut self, pending: AccountId) -> Result<(), Error> {
+            // check that the caller is the root user
+            let caller = Self::env().caller();
+            if self.root != caller {
+                return Err(Error::PermissionDenied)
+            }
+            if Self::is_zero_address(pending) {
+                return Err(Error::InvalidRoot)
+            }
+            self.pending_root = Some(pending);
+            Ok(())
+        }
 
-    }
-    // END OF CONTRACT LOGIC
+
+        // 10 🟢 ACCEPT ROOT (PENDING ROOT ONLY)
+        // second step of a root handoff, the nominated account takes over as root
+        #[ink(message)]
+        pub fn accept_root(&mut self) -> Result<(), Error> {
+            // check that the caller is the pending root user
+            let caller = Self::env().caller();
+            if self.pending_root != Some(caller) {
+                return Err(Error::PermissionDenied)
+            }
+
+            let old_root = self.root;
+            self.root = caller;
+            self.pending_root = None;
+
+            // emit an event only if the root actually changed
+            if old_root != caller {
+                Self::env().emit_event(RootChangedEvent {
+                    old_root,
+                    new_root: caller,
+                    timestamp: self.env().block_timestamp(),
+                });
+            }
+            Ok(())
+        }
+
+
+        // 11 🟢 GET USER DETAILS
+        // returns the full Pebble record for a user, or None if they never got coin
+        // (a pruned account only has its last claim time and lifetime payout left)
+        #[ink(message)]
+        pub fn get_user_details(&self, user: AccountId) -> Option<Pebble> {
+            if !self.lookup_allowed(user) {
+                return None
+            }
+            self.user_record(user)
+        }
+
+
+        // 12 🟢 BLOCK ACCOUNT (ADMIN ONLY)
+        // lets an admin stop an abusive account from getting coin
+        #[ink(message)]
+        pub fn block_account(&mut self, who: AccountId) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            self.blocklist.insert(who, &true);
+            Self::env().emit_event(AccountBlockedEvent {
+                account: who,
+                admin: caller,
+                timestamp: self.env().block_timestamp(),
+            });
+            Ok(())
+        }
+
+
+        // 13 🟢 UNBLOCK ACCOUNT (ADMIN ONLY)
+        // lets an admin remove an account from the blocklist
+        #[ink(message)]
+        pub fn unblock_account(&mut self, who: AccountId) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            self.blocklist.remove(who);
+            Self::env().emit_event(AccountUnblockedEvent {
+                account: who,
+                admin: caller,
+                timestamp: self.env().block_timestamp(),
+            });
+            Ok(())
+        }
+
+
+        // 14 🟢 IS BLOCKED
+        // returns true if the account is on the blocklist
+        #[ink(message)]
+        pub fn is_blocked(&self, who: AccountId) -> bool {
+            self.blocklist.get(who).unwrap_or_default()
+        }
+
+
+        // 15 🟢 BLOCK IP ADDRESS (ADMIN ONLY)
+        // lets an admin stop an abusive IP address from getting coin
+        #[ink(message)]
+        pub fn block_ip(&mut self, ip: Vec<u8>) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            let ip_key = Self::hash_ip(&ip);
+            self.blocked_ips.insert(ip_key.clone(), &true);
+            Self::env().emit_event(IpBlockedEvent {
+                user_ip: ip_key,
+                admin: caller,
+                timestamp: self.env().block_timestamp(),
+            });
+            Ok(())
+        }
+
+
+        // 16 🟢 UNBLOCK IP ADDRESS (ADMIN ONLY)
+        // lets an admin remove an IP address from the blocklist
+        #[ink(message)]
+        pub fn unblock_ip(&mut self, ip: Vec<u8>) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            let ip_key = Self::hash_ip(&ip);
+            self.blocked_ips.remove(ip_key.clone());
+            Self::env().emit_event(IpUnblockedEvent {
+                user_ip: ip_key,
+                admin: caller,
+                timestamp: self.env().block_timestamp(),
+            });
+            Ok(())
+        }
+
+
+        // 17 🟢 IS IP BLOCKED
+        // returns true if the IP address is on the blocklist
+        #[ink(message)]
+        pub fn is_ip_blocked(&self, ip: Vec<u8>) -> bool {
+            self.blocked_ips.get(Self::hash_ip(&ip)).unwrap_or_default()
+        }
+
+
+        // 18 🟢 ELIGIBILITY REASON [ANYONE]
+        // read only check that explains why the caller can or cannot get coin right now
+        #[ink(message)]
+        pub fn eligibility_reason(&self, my_ip_address: Vec<u8>) -> EligibilityStatus {
+            let caller = Self::env().caller();
+            let now = self.env().block_timestamp();
+            match self.check_claim_allowed(caller, my_ip_address.clone(), None, false, 0) {
+                Ok(_) => EligibilityStatus::Eligible,
+                Err(Error::Paused) => EligibilityStatus::Paused,
+                Err(Error::InvalidIpAddress) => EligibilityStatus::InvalidIpAddress,
+                Err(Error::AccountBlocked) | Err(Error::IpBlocked) => EligibilityStatus::Blocked,
+                Err(Error::NotAllowlisted) => EligibilityStatus::NotAllowlisted,
+                Err(Error::LifetimeCapReached) => EligibilityStatus::LifetimeCapReached,
+                Err(Error::IpLimitReached) => EligibilityStatus::IpLimitReached,
+                Err(Error::RateLimited) => EligibilityStatus::RateLimited {
+                    seconds_remaining: self.seconds_until_eligible(caller),
+                },
+                Err(Error::IpRateLimited) => EligibilityStatus::RateLimited {
+                    seconds_remaining: self.ip_time_remaining(0, Self::hash_ip(&my_ip_address), now),
+                },
+                Err(Error::DailyLimitReached) => EligibilityStatus::DailyLimitReached,
+                Err(Error::MaxAccountsReached) => EligibilityStatus::MaxAccountsReached,
+                Err(Error::BlockRateLimited) => EligibilityStatus::BlockRateLimited,
+                Err(Error::TooManyIps) => EligibilityStatus::TooManyIps,
+                Err(Error::AccountTooNew) => EligibilityStatus::AccountTooNew,
+                Err(Error::IpAlreadyUsed) => EligibilityStatus::IpAlreadyUsed,
+                Err(Error::PayoutBelowMinimum) | Err(Error::FeeTooHigh) => EligibilityStatus::PayoutBelowMinimum,
+                Err(Error::FaucetClosed) => EligibilityStatus::FaucetClosed,
+                // anything else is the balance falling short
+                Err(_) => EligibilityStatus::ContractDrained,
+            }
+        }
+
+
+        // 19 🟢 SECONDS UNTIL ELIGIBLE
+        // returns how long until the user's timer lets them get coin again
+        // time is in the same units as limit_timer, 0 means they can claim now
+        #[ink(message)]
+        pub fn seconds_until_eligible(&self, user: AccountId) -> u64 {
+            let now = self.env().block_timestamp();
+            let user_details = self.user_record(user).unwrap_or_default();
+            // accounts that never got coin can claim right away, 
+            // unless new accounts have to wait out the timer too
+            // (this keys off the account being new, not its payout being zero)
+            if !self.has_claimed(user) && self.new_account_bypass_timer {
+                return 0;
+            }
+            let time_since = now.wrapping_sub(user_details.timestamp);
+            self.limit_timer.saturating_sub(time_since)
+        }
+
+
+        // 20 🟢 BATCH PAYOUT (ROOT ONLY)
+        // lets the root account send the same amount of coin to many accounts at once
+        // returns the number of recipients that were paid, the others are counted
+        // in total_failed_payouts
+        #[ink(message)]
+        pub fn batch_payout(&mut self, 
+            recipients: Vec<AccountId>,
+            amount: Balance
+        ) -> Result<u32, Error> {
+            // check that the caller is the root user
+            let caller = Self::env().caller();
+            if self.root != caller {
+                return Err(Error::PermissionDenied)
+            }
+
+            // cap the batch size to keep the call within gas limits
+            if recipients.len() > MAX_BATCH_RECIPIENTS {
+                return Err(Error::TooManyRecipients)
+            }
+
+            // reject the whole batch if any recipient is the zero address
+            if recipients.iter().any(|recipient| Self::is_zero_address(*recipient)) {
+                return Err(Error::InvalidRecipient)
+            }
+
+            let now = self.env().block_timestamp();
+            let mut paid: u32 = 0;
+            for recipient in recipients {
+                // skip any recipient the contract can no longer cover without
+                // touching coin held for pending claims, or whose transfer
+                // fails, but keep count of them
+                // (a failed get_coin transfer reverts the whole call, so only
+                // failures that are skipped here can be recorded)
+                if self.env().balance().saturating_sub(self.pending_total) < amount
+                || self.env().transfer(recipient, amount).is_err() {
+                    self.total_failed_payouts = self.total_failed_payouts.saturating_add(1);
+                    continue;
+                }
+                paid = paid.saturating_add(1);
+                self.record_payout(now, amount);
+
+                // emit an event for each payout
+                Self::env().emit_event(PayoutEvent {
+                    timestamp: now,
+                    user_ip: Vec::new(),
+                    pebble: recipient,
+                    payout: amount,
+                });
+            }
+
+            Ok(paid)
+        }
+
+
+        // 21 🟢 GET COIN WITH REFERRER [ANYONE]
+        // like get_coin, but on a new user's first claim the friend who
+        // referred them also gets the referrer_payout
+        #[ink(message)]
+        pub fn get_coin_with_referrer(&mut self, 
+            my_ip_address: Vec<u8>,
+            referrer: AccountId
+        ) -> Result<Balance, Error> {
+            // an account cannot refer itself
+            let caller = Self::env().caller();
+            if referrer == caller {
+                return Err(Error::SelfReferral)
+            }
+            // the referrer_payout would be burned on the zero address
+            if Self::is_zero_address(referrer) {
+                return Err(Error::InvalidRecipient)
+            }
+            self.pay_claim(caller, my_ip_address, Some(referrer), false, false, 0)
+        }
+
+
+        // 22 🟢 SET REFERRER PAYOUT (ADMIN ONLY)
+        // lets an admin set the coin paid to a referrer per new friend
+        #[ink(message)]
+        pub fn set_referrer_payout(&mut self, new_referrer_payout: Balance) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            if !self.within_payout_cap(new_referrer_payout) {
+                return Err(Error::PayoutTooHigh)
+            }
+            self.referrer_payout = new_referrer_payout;
+            Ok(())
+        }
+
+
+        // 23 🟢 REFERRAL COUNT
+        // returns how many new users this account has referred
+        #[ink(message)]
+        pub fn referral_count(&self, who: AccountId) -> u128 {
+            if !self.lookup_allowed(who) {
+                return 0
+            }
+            self.referral_counts.get(who).unwrap_or_default()
+        }
+
+
+        // 24 🟢 LIST ACCOUNTS
+        // returns one page of all the pebble accounts, limit is capped at MAX_PAGE_SIZE
+        #[ink(message)]
+        pub fn list_accounts(&self, start: u32, limit: u32) -> Vec<AccountId> {
+            if !self.verify_allowed() {
+                return Vec::new()
+            }
+            let end = start.saturating_add(limit.min(MAX_PAGE_SIZE)).min(self.account_count);
+            (start..end)
+                .filter_map(|index| self.accounts.get(index))
+                .collect()
+        }
+
+
+        // 25 🟢 ACCOUNTS FOR IP
+        // returns all the accounts that have tagged the faucet from this IP address
+        #[ink(message)]
+        pub fn accounts_for_ip(&self, ip: Vec<u8>) -> Vec<AccountId> {
+            if !self.verify_allowed() {
+                return Vec::new()
+            }
+            self.ipaddress_count.get(Self::hash_ip(&ip)).unwrap_or_default()
+        }
+
+
+        // 26 🟢 IP ACCOUNT COUNT
+        // returns how many accounts have tagged the faucet from this IP address
+        #[ink(message)]
+        pub fn ip_account_count(&self, ip: Vec<u8>) -> u128 {
+            let ip_tags = self.ipaddress_count.get(Self::hash_ip(&ip)).unwrap_or_default();
+            Self::tag_count(&ip_tags)
+        }
+
+
+        // 27 🟢 SWEEP ALL FUNDS (ROOT ONLY)
+        // lets the root account recover the entire contract balance, less any coin held
+        // for pending claims, returns the amount swept
+        #[ink(message)]
+        pub fn sweep_all(&mut self, to: AccountId) -> Result<Balance, Error> {
+            // check that the caller is the root user
+            let caller = Self::env().caller();
+            if self.root != caller {
+                return Err(Error::PermissionDenied)
+            }
+            if Self::is_zero_address(to) {
+                return Err(Error::InvalidRecipient)
+            }
+
+            // send the whole balance, except coin held for pending claims
+            let amount = self.env().balance().saturating_sub(self.pending_total);
+            if self.env().transfer(to, amount).is_err() {
+                return Err(Error::PayoutFailed)
+            }
+
+            // emit an event for the withdrawal
+            Self::env().emit_event(WithdrawEvent {
+                timestamp: self.env().block_timestamp(),
+                to,
+                amount,
+            });
+
+            Ok(amount)
+        }
+
+
+        // 28 🟢 SET ALLOWLIST ONLY (ADMIN ONLY)
+        // lets an admin make the faucet pay allowlisted accounts only
+        #[ink(message)]
+        pub fn set_allowlist_only(&mut self, allowlist_only: bool) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            self.allowlist_only = allowlist_only;
+            Ok(())
+        }
+
+
+        // 29 🟢 ALLOW ACCOUNT (ADMIN ONLY)
+        // lets an admin add an account to the allowlist
+        #[ink(message)]
+        pub fn allow_account(&mut self, who: AccountId) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            self.allowlist.insert(who, &true);
+            Ok(())
+        }
+
+
+        // 30 🟢 DISALLOW ACCOUNT (ADMIN ONLY)
+        // lets an admin remove an account from the allowlist
+        #[ink(message)]
+        pub fn disallow_account(&mut self, who: AccountId) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            self.allowlist.remove(who);
+            Ok(())
+        }
+
+
+        // 31 🟢 CONTRACT BALANCE
+        // returns the coin currently held by the faucet
+        #[ink(message)]
+        pub fn contract_balance(&self) -> Balance {
+            self.env().balance()
+        }
+
+
+        // 32 🟢 SET IP LIMIT TIMER (ADMIN ONLY)
+        // lets an admin set how long an IP address must wait between claims
+        #[ink(message)]
+        pub fn set_ip_limit_timer(&mut self, new_ip_limit_timer: u64) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            self.ip_limit_timer = new_ip_limit_timer;
+            Ok(())
+        }
+
+
+        // 33 🟢 SET RESERVE (ADMIN ONLY)
+        // lets an admin set a balance the faucet will not pay out below
+        #[ink(message)]
+        pub fn set_reserve(&mut self, new_reserve: Balance) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            self.reserve = new_reserve;
+            Ok(())
+        }
+
+
+        // 34 🟢 RESET USER TIMER (ADMIN ONLY)
+        // lets an admin make a user eligible again right away
+        #[ink(message)]
+        pub fn reset_user_timer(&mut self, user: AccountId) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            // unknown users are left alone
+            if let Some(mut user_details) = self.user_map.get(user) {
+                user_details.timestamp = 0;
+                self.user_map.insert(user, &user_details);
+            }
+            Ok(())
+        }
+
+
+        // 35 🟢 CLEAR IP ADDRESS (ADMIN ONLY)
+        // lets an admin clear the accounts tagged to an IP address
+        // so a shared IP address can start fresh
+        #[ink(message)]
+        pub fn clear_ip(&mut self, ip: Vec<u8>) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            let ip_key = Self::hash_ip(&ip);
+            if self.ipaddress_count.contains(ip_key.clone()) {
+                self.ipaddress_count.remove(ip_key.clone());
+                self.total_unique_ips = self.total_unique_ips.saturating_sub(1);
+            }
+            self.ip_last_claim.remove(ip_key);
+            Ok(())
+        }
+
+
+        // 36 🟢 CAN CLAIM [ANYONE]
+        // dry run of get_coin for a user, returns the error get_coin would hit
+        // without transferring any coin or changing storage
+        #[ink(message)]
+        pub fn can_claim(&self, user: AccountId, ip: Vec<u8>) -> Result<(), Error> {
+            self.check_claim_allowed(user, ip, None, false, 0).map(|_| ())
+        }
+
+
+        // 37 🟢 SET TOKEN DECIMALS (ADMIN ONLY)
+        // lets an admin record the chain's decimals so front-ends display
+        // payouts consistently, this is informational only
+        #[ink(message)]
+        pub fn set_token_decimals(&mut self, new_token_decimals: u8) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            self.token_decimals = new_token_decimals;
+            Ok(())
+        }
+
+
+        // 38 🟢 SET MAX ACCOUNTS (ADMIN ONLY)
+        // lets an admin cap the number of accounts, 0 means unlimited
+        #[ink(message)]
+        pub fn set_max_accounts(&mut self, new_max_accounts: u128) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            self.max_accounts = new_max_accounts;
+            Ok(())
+        }
+
+
+        // 39 🟢 TERMINATE (ROOT ONLY)
+        // lets the root account decommission the faucet, the remaining
+        // balance is sent to the beneficiary and the contract is removed
+        // refused while any claim is pending, so coin held for queued claims
+        // is never swept to the beneficiary
+        #[ink(message)]
+        pub fn terminate(&mut self, beneficiary: AccountId) -> Result<(), Error> {
+            // check that the caller is the root user
+            let caller = Self::env().caller();
+            if self.root != caller {
+                return Err(Error::PermissionDenied)
+            }
+            if Self::is_zero_address(beneficiary) {
+                return Err(Error::InvalidRecipient)
+            }
+            if self.pending_total > 0 {
+                return Err(Error::ClaimsPending)
+            }
+
+            // emit a final event before the contract is removed
+            Self::env().emit_event(TerminatedEvent {
+                beneficiary,
+                timestamp: self.env().block_timestamp(),
+                amount: self.env().balance(),
+            });
+
+            self.env().terminate_contract(beneficiary)
+        }
+
+
+        // 40 🟢 SET MAX CLAIMS PER BLOCK (ADMIN ONLY)
+        // lets an admin cap the claims paid in a single block, 0 means unlimited
+        #[ink(message)]
+        pub fn set_max_claims_per_block(&mut self, new_max_claims_per_block: u32) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            self.max_claims_per_block = new_max_claims_per_block;
+            Ok(())
+        }
+
+
+        // 41 🟢 ADD ADMIN (ROOT ONLY)
+        // lets the root account give an account admin rights
+        #[ink(message)]
+        pub fn add_admin(&mut self, who: AccountId) -> Result<(), Error> {
+            // check that the caller is the root user
+            let caller = Self::env().caller();
+            if self.root != caller {
+                return Err(Error::PermissionDenied)
+            }
+            self.admins.insert(who, &true);
+            Ok(())
+        }
+
+
+        // 42 🟢 REMOVE ADMIN (ROOT ONLY)
+        // lets the root account take away an account's admin rights
+        #[ink(message)]
+        pub fn remove_admin(&mut self, who: AccountId) -> Result<(), Error> {
+            // check that the caller is the root user
+            let caller = Self::env().caller();
+            if self.root != caller {
+                return Err(Error::PermissionDenied)
+            }
+            self.admins.remove(who);
+            Ok(())
+        }
+
+
+        // 43 🟢 GET DASHBOARD
+        // returns the stats and settings data together with the live contract balance
+        #[ink(message)]
+        pub fn get_dashboard(&self) -> DashboardView {
+            DashboardView {
+                stats: self.get_stats_and_settings(),
+                contract_balance: self.env().balance(),
+            }
+        }
+
+
+        // 44 🟢 PAYOUTS IN WINDOW
+        // returns the coin paid out in the last window_ms milliseconds, to the
+        // nearest hour and looking back at most one day
+        #[ink(message)]
+        pub fn payouts_in_window(&self, window_ms: u64) -> Balance {
+            let now = self.env().block_timestamp();
+            let current_hour = now / HOUR_MS;
+            let first_hour = now.saturating_sub(window_ms) / HOUR_MS;
+            self.payout_buckets.iter()
+                .filter(|(hour, _)| {
+                    *hour >= first_hour 
+                    && *hour <= current_hour
+                    && current_hour - *hour < PAYOUT_BUCKETS
+                })
+                .fold(0, |total: Balance, (_, amount)| total.saturating_add(*amount))
+        }
+
+
+        // 45 🟢 SET DECAY ENABLED (ADMIN ONLY)
+        // lets an admin turn the payout decay curve on or off
+        #[ink(message)]
+        pub fn set_decay_enabled(&mut self, new_decay_enabled: bool) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            self.decay_enabled = new_decay_enabled;
+            Ok(())
+        }
+
+
+        // 46 🟢 IS ROOT
+        // returns true if the given account is the root account
+        #[ink(message)]
+        pub fn is_root(&self, who: AccountId) -> bool {
+            self.root == who
+        }
+
+
+        // 47 🟢 GET ROOT
+        // returns the root account, which the constructor always sets
+        #[ink(message)]
+        pub fn get_root(&self) -> AccountId {
+            self.root
+        }
+
+
+        // 48 🟢 VERIFY ACCOUNTS
+        // batch version of verify_account, returns a result for each of the
+        // first MAX_VERIFY_ACCOUNTS accounts given
+        #[ink(message)]
+        pub fn verify_accounts(&self, accounts: Vec<AccountId>) -> Vec<bool> {
+            let allowed = self.verify_allowed();
+            accounts.iter()
+                .take(MAX_VERIFY_ACCOUNTS)
+                .map(|account| allowed && self.has_claimed(*account))
+                .collect()
+        }
+
+
+        // 49 🟢 SET EVENTS ENABLED (ADMIN ONLY)
+        // lets an admin turn claim events off to save gas on busy faucets
+        #[ink(message)]
+        pub fn set_events_enabled(&mut self, new_events_enabled: bool) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            self.events_enabled = new_events_enabled;
+            Ok(())
+        }
+
+
+        // 50 🟢 GET NONCE
+        // returns how many successful claims this account has made
+        #[ink(message)]
+        pub fn get_nonce(&self, who: AccountId) -> u64 {
+            if !self.lookup_allowed(who) {
+                return 0
+            }
+            self.nonce_of(who)
+        }
+
+
+        // 51 🟢 FUND [ANYONE]
+        // lets anyone send coin to the faucet without changing any settings
+        #[ink(message, payable)]
+        pub fn fund(&mut self) -> Result<(), Error> {
+            self.record_funding();
+            Ok(())
+        }
+
+
+        // 52 🟢 CLAIM HISTORY
+        // returns the (timestamp, payout) of an account's most recent claims, oldest first
+        #[ink(message)]
+        pub fn claim_history(&self, who: AccountId) -> Vec<ClaimRecord> {
+            if !self.lookup_allowed(who) {
+                return Vec::new()
+            }
+            self.claim_history.get(who).unwrap_or_default()
+        }
+
+
+        // 53 🟢 SET MAX IPS PER ACCOUNT (ADMIN ONLY)
+        // lets an admin cap the distinct IP addresses one account can use, 0 means unlimited
+        #[ink(message)]
+        pub fn set_max_ips_per_account(&mut self, new_max_ips_per_account: u128) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            self.max_ips_per_account = new_max_ips_per_account;
+            Ok(())
+        }
+
+
+        // 54 🟢 PREVIEW PAYOUT
+        // returns the amount get_coin would pay this user right now, taking
+        // first claim, referral bonus, decay, streak bonus and claim fee rules into account
+        #[ink(message)]
+        pub fn preview_payout(&self, user: AccountId) -> Balance {
+            let (newuser, user_details) = self.pool_user(0, user);
+            let now = self.env().block_timestamp();
+            let streak = self.next_streak(&user_details, newuser, now, self.limit_timer);
+            self.claim_payout(user, newuser, streak, 0).0
+        }
+
+
+        // 55 🟢 PRUNE STALE ACCOUNTS (ROOT ONLY)
+        // lets the root account remove accounts that have not claimed in older_than_ms
+        // from the given candidates (at most MAX_PAGE_SIZE per call), returns the number pruned
+        // a pruned account keeps its last claim time, lifetime payout and claim nonce,
+        // so it is never treated as a new user again (no first claim payout or referral)
+        // and old signed claims cannot be replayed, everything else kept per account
+        // (claim history, first seen, IP addresses, referral count, streak) is removed
+        // accounts with a pending claim are skipped
+        #[ink(message)]
+        pub fn prune_stale(&mut self, 
+            accounts: Vec<AccountId>, 
+            older_than_ms: u64
+        ) -> Result<u32, Error> {
+            // check that the caller is the root user
+            let caller = Self::env().caller();
+            if self.root != caller {
+                return Err(Error::PermissionDenied)
+            }
+
+            let cutoff = self.env().block_timestamp().saturating_sub(older_than_ms);
+            let mut pruned: u32 = 0;
+            for account in accounts.into_iter().take(MAX_PAGE_SIZE as usize) {
+                if let Some(user_details) = self.user_map.get(account) {
+                    if user_details.timestamp < cutoff && !self.pending_claims.contains(account) {
+                        let nonce = self.claim_nonce.take(account).unwrap_or_default();
+                        self.user_map.remove(account);
+                        self.pruned_accounts.insert(account, &(user_details.timestamp, user_details.payout, nonce));
+                        self.claim_history.remove(account);
+                        self.first_seen.remove(account);
+                        self.ips_seen.remove(account);
+                        self.referral_counts.remove(account);
+                        self.decrement_accounts(account);
+                        pruned = pruned.saturating_add(1);
+                    }
+                }
+            }
+            Ok(pruned)
+        }
+
+
+        // 56 🟢 SET NEW ACCOUNT BYPASS TIMER (ADMIN ONLY)
+        // lets an admin choose whether brand new accounts skip the claim timer
+        #[ink(message)]
+        pub fn set_new_account_bypass_timer(&mut self, new_new_account_bypass_timer: bool) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            self.new_account_bypass_timer = new_new_account_bypass_timer;
+            Ok(())
+        }
+
+
+        // 57 🟢 CLAIM FOR [RELAYER]
+        // lets a relayer pay the gas for a claim signed by the beneficiary, the
+        // beneficiary (not the caller) must be eligible and is the one paid
+        // the signature is a 65 byte ECDSA signature, made with the key the beneficiary
+        // registered with register_signer, over the Blake2x256 hash of the SCALE
+        // encoded (faucet, b"claim_for", beneficiary, ip, nonce), where faucet is this
+        // contract's address and nonce is get_nonce(beneficiary)
+        #[ink(message)]
+        pub fn claim_for(&mut self, 
+            beneficiary: AccountId,
+            ip: Vec<u8>,
+            signature: Vec<u8>,
+            nonce: u64
+        ) -> Result<Balance, Error> {
+            self.verify_signed_claim(b"claim_for", beneficiary, &ip, signature, nonce)?;
+            self.pay_claim(beneficiary, ip, None, false, false, 0)
+        }
+
+
+        // 58 🟢 SET RESTRICT VERIFY (ADMIN ONLY)
+        // lets an admin limit verify_account and the per user lookups (user details,
+        // claim history, streak, nonce, first seen, referrals, pending claim, the
+        // account list and accounts for an IP address) to allowlisted verifiers,
+        // accounts can still look up their own data. Relayers need to be allowlisted
+        // to read get_nonce for the accounts they claim for.
+        #[ink(message)]
+        pub fn set_restrict_verify(&mut self, new_restrict_verify: bool) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            self.restrict_verify = new_restrict_verify;
+            Ok(())
+        }
+
+
+        // 59 🟢 ALLOW VERIFIER (ADMIN ONLY)
+        // lets an admin add a contract or account to the verifier allowlist
+        #[ink(message)]
+        pub fn allow_verifier(&mut self, who: AccountId) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            self.verifier_allowlist.insert(who, &true);
+            Ok(())
+        }
+
+
+        // 60 🟢 DISALLOW VERIFIER (ADMIN ONLY)
+        // lets an admin remove a contract or account from the verifier allowlist
+        #[ink(message)]
+        pub fn disallow_verifier(&mut self, who: AccountId) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            self.verifier_allowlist.remove(who);
+            Ok(())
+        }
+
+
+        // 61 🟢 SET REWARD TOKEN (ROOT ONLY)
+        // lets the root account drip a PSP22 token alongside the native coin,
+        // set token to None to turn it off. The faucet contract must hold the tokens.
+        // strict makes a failed token transfer revert the whole claim
+        #[ink(message)]
+        pub fn set_reward_token(&mut self, 
+            token: Option<AccountId>,
+            amount: Balance,
+            strict: bool
+        ) -> Result<(), Error> {
+            // check that the caller is the root user
+            let caller = Self::env().caller();
+            if self.root != caller {
+                return Err(Error::PermissionDenied)
+            }
+            self.reward_token = token;
+            self.token_amount = amount;
+            self.token_strict = strict;
+            Ok(())
+        }
+
+
+        // 62 🟢 SET GET PAYOUT (ADMIN ONLY)
+        // lets an admin change just the get_payout setting, leaving the others as they are
+        #[ink(message)]
+        pub fn set_get_payout(&mut self, new_get_payout: Balance) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            if !self.within_payout_cap(new_get_payout) {
+                return Err(Error::PayoutTooHigh)
+            }
+            // the claim fee must leave something of the payout
+            if !Self::fee_allowed(self.claim_fee, new_get_payout) {
+                return Err(Error::FeeTooHigh)
+            }
+            self.get_payout = new_get_payout;
+            self.emit_settings_changed();
+            Ok(())
+        }
+
+
+        // 63 🟢 SET ELIGIBLE PAYOUT (ADMIN ONLY)
+        // lets an admin change just the eligible_payout setting, leaving the others as they are
+        #[ink(message)]
+        pub fn set_eligible_payout(&mut self, new_eligible_payout: Balance) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            if !self.within_payout_cap(new_eligible_payout) {
+                return Err(Error::PayoutTooHigh)
+            }
+            self.eligible_payout = new_eligible_payout;
+            self.emit_settings_changed();
+            Ok(())
+        }
+
+
+        // 64 🟢 SET LIMIT TIMER (ADMIN ONLY)
+        // lets an admin change just the limit_timer setting, leaving the others as they are
+        #[ink(message)]
+        pub fn set_limit_timer(&mut self, new_limit_timer: u64) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            self.limit_timer = new_limit_timer;
+            self.emit_settings_changed();
+            Ok(())
+        }
+
+
+        // 65 🟢 SET LIMIT IP TOTAL (ADMIN ONLY)
+        // lets an admin change just the limit_ip_total setting, leaving the others as they are
+        #[ink(message)]
+        pub fn set_limit_ip_total(&mut self, new_limit_ip_total: u128) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            self.limit_ip_total = new_limit_ip_total;
+            self.emit_settings_changed();
+            Ok(())
+        }
+
+
+        // 66 🟢 GET COIN WITH MEMO [ANYONE]
+        // like get_coin, but tags the claim with a short memo (e.g. a campaign code)
+        // the memo is not stored, it is only emitted in a ClaimMemoEvent
+        #[ink(message)]
+        pub fn get_coin_with_memo(&mut self, 
+            my_ip_address: Vec<u8>,
+            memo: Vec<u8>
+        ) -> Result<Balance, Error> {
+            if memo.len() > MAX_MEMO_LEN {
+                return Err(Error::MemoTooLong)
+            }
+            let caller = Self::env().caller();
+            let payout = self.pay_claim(caller, my_ip_address, None, false, false, 0)?;
+
+            // the memo event is emitted even when events are turned off,
+            // since the event is the only place the memo goes
+            Self::env().emit_event(ClaimMemoEvent {
+                timestamp: self.env().block_timestamp(),
+                pebble: caller,
+                memo,
+                payout,
+            });
+            Ok(payout)
+        }
+
+
+        // 67 🟢 GET COIN TO [ANYONE]
+        // lets the caller sponsor a claim for a friend, every check and timer
+        // applies to the recipient (not the caller) and the recipient is paid
+        #[ink(message)]
+        pub fn get_coin_to(&mut self, 
+            recipient: AccountId,
+            my_ip_address: Vec<u8>
+        ) -> Result<Balance, Error> {
+            if Self::is_zero_address(recipient) {
+                return Err(Error::InvalidRecipient)
+            }
+            // a blocked account cannot sponsor claims for others either
+            if self.is_blocked(Self::env().caller()) {
+                return Err(Error::AccountBlocked)
+            }
+            self.pay_claim(recipient, my_ip_address, None, false, false, 0)
+        }
+
+
+        // 68 🟢 MIGRATE (ROOT ONLY)
+        // lets the root account bump the storage version after an upgrade,
+        // new fields that need initializing after an upgrade get set up here
+        #[ink(message)]
+        pub fn migrate(&mut self) -> Result<(), Error> {
+            // check that the caller is the root user
+            let caller = Self::env().caller();
+            if self.root != caller {
+                return Err(Error::PermissionDenied)
+            }
+            // nothing to migrate yet, future migrations go here keyed
+            // on the current self.storage_version
+            self.storage_version = self.storage_version.saturating_add(1);
+            Ok(())
+        }
+
+
+        // 69 🟢 SET MIN ACCOUNT AGE MS (ADMIN ONLY)
+        // lets an admin set how long after check_in a new account must wait to claim, 0 turns it off
+        #[ink(message)]
+        pub fn set_min_account_age_ms(&mut self, new_min_account_age_ms: u64) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            self.min_account_age_ms = new_min_account_age_ms;
+            Ok(())
+        }
+
+
+        // 70 🟢 CHECK IN [ANYONE]
+        // records when the caller was first seen so the min_account_age_ms wait
+        // can start, returns the first seen time (unchanged if already recorded)
+        // (a claim that fails reverts, so new accounts check in separately)
+        #[ink(message)]
+        pub fn check_in(&mut self) -> u64 {
+            let caller = Self::env().caller();
+            match self.first_seen.get(caller) {
+                Some(first_seen) => first_seen,
+                None => {
+                    let now = self.env().block_timestamp();
+                    self.first_seen.insert(caller, &now);
+                    now
+                }
+            }
+        }
+
+
+        // 71 🟢 ATTEST FIRST SEEN (ADMIN ONLY)
+        // lets an admin record an off-chain attested first seen time for an account
+        #[ink(message)]
+        pub fn attest_first_seen(&mut self, who: AccountId, timestamp: u64) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            self.first_seen.insert(who, &timestamp);
+            Ok(())
+        }
+
+
+        // 72 🟢 GET FIRST SEEN
+        // returns when the account was first seen, if ever
+        #[ink(message)]
+        pub fn get_first_seen(&self, who: AccountId) -> Option<u64> {
+            if !self.lookup_allowed(who) {
+                return None
+            }
+            self.first_seen.get(who)
+        }
+
+
+        // 73 🟢 SET REFERRAL MULTIPLIER BPS (ADMIN ONLY)
+        // lets an admin set the payout bonus per referral in basis points, 0 turns it off
+        #[ink(message)]
+        pub fn set_referral_multiplier_bps(&mut self, new_referral_multiplier_bps: u16) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            self.referral_multiplier_bps = new_referral_multiplier_bps;
+            Ok(())
+        }
+
+
+        // 74 🟢 SET MAX PAYOUT CAP (ROOT ONLY)
+        // lets the root account set the highest payout an admin can configure,
+        // 0 means no cap. Payouts already set are not changed.
+        #[ink(message)]
+        pub fn set_max_payout_cap(&mut self, new_max_payout_cap: Balance) -> Result<(), Error> {
+            // check that the caller is the root user
+            let caller = Self::env().caller();
+            if self.root != caller {
+                return Err(Error::PermissionDenied)
+            }
+            self.max_payout_cap = new_max_payout_cap;
+            Ok(())
+        }
+
+
+        // 75 🟢 DAILY REMAINING
+        // returns how much of today's daily_limit is left to pay out,
+        // Balance::MAX when there is no daily limit
+        #[ink(message)]
+        pub fn daily_remaining(&self) -> Balance {
+            if self.daily_limit == 0 {
+                return Balance::MAX;
+            }
+            let (_, day_spent) = self.current_day(self.env().block_timestamp());
+            self.daily_limit.saturating_sub(day_spent)
+        }
+
+
+        // 76 🟢 SET METADATA (ROOT ONLY)
+        // lets the root account give the faucet a name and description so
+        // directory apps can tell deployed faucets apart
+        #[ink(message)]
+        pub fn set_metadata(&mut self, 
+            name: Vec<u8>,
+            description: Vec<u8>
+        ) -> Result<(), Error> {
+            // check that the caller is the root user
+            let caller = Self::env().caller();
+            if self.root != caller {
+                return Err(Error::PermissionDenied)
+            }
+            if name.len() > MAX_NAME_LEN || description.len() > MAX_DESCRIPTION_LEN {
+                return Err(Error::MetadataTooLong)
+            }
+            self.name = name;
+            self.description = description;
+            Ok(())
+        }
+
+
+        // 77 🟢 GET METADATA
+        // returns the faucet (name, description)
+        #[ink(message)]
+        pub fn get_metadata(&self) -> (Vec<u8>, Vec<u8>) {
+            (self.name.clone(), self.description.clone())
+        }
+
+
+        // 78 🟢 SET ONE ACCOUNT PER IP (ADMIN ONLY)
+        // lets an admin allow only one account per IP address, ever
+        #[ink(message)]
+        pub fn set_one_account_per_ip(&mut self, new_one_account_per_ip: bool) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            self.one_account_per_ip = new_one_account_per_ip;
+            Ok(())
+        }
+
+
+        // 79 🟢 SET BYPASS CODE (ROOT ONLY)
+        // lets the root account set the blake2x256 hash of a secret code for
+        // private events, None turns bypass codes off. Only the hash is stored,
+        // but the code itself is sent in plain call data by every get_coin_with_code
+        // and anyone watching the chain can read it, so set a new code for each event
+        // and turn it off once the event is over
+        #[ink(message)]
+        pub fn set_bypass_code_hash(&mut self, new_bypass_code_hash: Option<Hash>) -> Result<(), Error> {
+            // check that the caller is the root user
+            let caller = Self::env().caller();
+            if self.root != caller {
+                return Err(Error::PermissionDenied)
+            }
+            self.bypass_code_hash = new_bypass_code_hash;
+            Ok(())
+        }
+
+
+        // 80 🟢 GET COIN WITH CODE [ANYONE]
+        // like get_coin, but a correct bypass code skips the timers and IP address
+        // limits (blocklists, caps and the balance still apply), a wrong code
+        // is just a normal get_coin
+        // each account can use a code only once, after that (or once the code
+        // has leaked from the call data) it is a normal get_coin for that account
+        #[ink(message)]
+        pub fn get_coin_with_code(&mut self, 
+            my_ip_address: Vec<u8>,
+            code: Vec<u8>
+        ) -> Result<Balance, Error> {
+            let caller = Self::env().caller();
+            let code_hash = Hash::from(self.env().hash_bytes::<Blake2x256>(&code));
+            let skip_limits = self.bypass_code_hash == Some(code_hash)
+                && self.bypass_codes_used.get(caller) != Some(code_hash);
+            // the code is used up before any coin is transferred
+            if skip_limits {
+                self.bypass_codes_used.insert(caller, &code_hash);
+            }
+            self.pay_claim(caller, my_ip_address, None, skip_limits, false, 0)
+        }
+
+
+        // 81 🟢 RESET STATS (ROOT ONLY)
+        // lets the root account zero the cumulative counters (payouts, failures,
+        // fees and the payout window) for a new campaign. The live gauges
+        // total_pebble_accounts and total_unique_ips are kept, since they count the
+        // accounts and IP addresses still stored, and so is all per user and per IP
+        // address data, including the account index that max_accounts is checked against
+        #[ink(message)]
+        pub fn reset_stats(&mut self) -> Result<(), Error> {
+            // check that the caller is the root user
+            let caller = Self::env().caller();
+            if self.root != caller {
+                return Err(Error::PermissionDenied)
+            }
+
+            Self::env().emit_event(StatsResetEvent {
+                root: caller,
+                timestamp: self.env().block_timestamp(),
+                total_payouts: self.total_payouts,
+                total_pebble_accounts: self.total_pebble_accounts,
+            });
+
+            self.total_payouts = 0;
+            self.total_failed_payouts = 0;
+            self.total_failed_token_transfers = 0;
+            self.total_fees_collected = 0;
+            self.payout_buckets = Vec::default();
+            self.stats_overflowed = false;
+            for pool_id in self.pool_ids.clone() {
+                if let Some(mut pool) = self.pools.get(pool_id) {
+                    pool.paid_out = 0;
+                    pool.claims = 0;
+                    self.pools.insert(pool_id, &pool);
+                }
+            }
+            Ok(())
+        }
+
+
+        // 82 🟢 SET MIN TRANSFER (ADMIN ONLY)
+        // lets an admin set the smallest payout the faucet will send (e.g. the
+        // chain's existential deposit), bump raises smaller payouts to it
+        // instead of rejecting the claim
+        #[ink(message)]
+        pub fn set_min_transfer(&mut self, 
+            new_min_transfer: Balance,
+            bump: bool
+        ) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            // with bump on, min_transfer is a payout like any other
+            if !self.within_payout_cap(new_min_transfer) {
+                return Err(Error::PayoutTooHigh)
+            }
+            self.min_transfer = new_min_transfer;
+            self.min_transfer_bump = bump;
+            Ok(())
+        }
+
+
+        // 83 🟢 ELIGIBLE PAYOUT
+        // returns just the eligible_payout setting, cheaper to cross-call than get_stats_and_settings
+        #[ink(message)]
+        pub fn eligible_payout(&self) -> Balance {
+            self.eligible_payout
+        }
+
+
+        // 84 🟢 GET PAYOUT AMOUNT
+        // returns just the get_payout setting
+        #[ink(message)]
+        pub fn get_payout_amount(&self) -> Balance {
+            self.get_payout
+        }
+
+
+        // 85 🟢 LIMIT TIMER
+        // returns just the limit_timer setting
+        #[ink(message)]
+        pub fn limit_timer(&self) -> u64 {
+            self.limit_timer
+        }
+
+
+        // 86 🟢 LIMIT IP TOTAL
+        // returns just the limit_ip_total setting
+        #[ink(message)]
+        pub fn limit_ip_total(&self) -> u128 {
+            self.limit_ip_total
+        }
+
+
+        // 87 🟢 SET STREAK BONUS (ADMIN ONLY)
+        // lets an admin set the extra coin paid on claims that keep a streak going, 0 turns it off
+        #[ink(message)]
+        pub fn set_streak_bonus(&mut self, new_streak_bonus: Balance) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            if !self.within_payout_cap(new_streak_bonus) {
+                return Err(Error::PayoutTooHigh)
+            }
+            self.streak_bonus = new_streak_bonus;
+            Ok(())
+        }
+
+
+        // 88 🟢 GET STREAK
+        // returns how many claims in a row the account has made, each within
+        // twice the limit_timer of the one before
+        #[ink(message)]
+        pub fn get_streak(&self, who: AccountId) -> u32 {
+            if !self.lookup_allowed(who) {
+                return 0
+            }
+            self.user_map.get(who).unwrap_or_default().streak
+        }
+
+
+        // 89 🟢 SET CLAIM WINDOW (ADMIN ONLY)
+        // lets an admin limit claims to a timed campaign between open_time and
+        // close_time (block timestamps in ms), 0 leaves that end unbounded
+        #[ink(message)]
+        pub fn set_claim_window(&mut self, 
+            new_open_time: u64,
+            new_close_time: u64
+        ) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            self.open_time = new_open_time;
+            self.close_time = new_close_time;
+            Ok(())
+        }
+
+
+        // 90 🟢 TOTAL FUNDED
+        // returns the total coin ever sent in to fund the faucet
+        #[ink(message)]
+        pub fn total_funded(&self) -> Balance {
+            self.total_funded
+        }
+
+
+        // 91 🟢 IP PAYOUT TOTAL
+        // returns the total coin ever paid out to claims from this IP address
+        #[ink(message)]
+        pub fn ip_payout_total(&self, ip: Vec<u8>) -> Balance {
+            self.ip_total_payout.get(Self::hash_ip(&ip)).unwrap_or_default()
+        }
+
+
+        // 92 🟢 ADD MAINTENANCE WINDOW (ROOT ONLY)
+        // lets the root account schedule a blackout from start to end (block
+        // timestamps in ms) when the faucet will not pay out
+        #[ink(message)]
+        pub fn add_maintenance_window(&mut self, 
+            start: u64,
+            end: u64
+        ) -> Result<(), Error> {
+            // check that the caller is the root user
+            let caller = Self::env().caller();
+            if self.root != caller {
+                return Err(Error::PermissionDenied)
+            }
+            if self.maintenance_windows.len() >= MAX_MAINTENANCE_WINDOWS {
+                return Err(Error::TooManyWindows)
+            }
+            self.maintenance_windows.push((start, end));
+            Ok(())
+        }
+
+
+        // 93 🟢 CLEAR MAINTENANCE WINDOWS (ROOT ONLY)
+        // lets the root account remove every scheduled maintenance window
+        #[ink(message)]
+        pub fn clear_maintenance_windows(&mut self) -> Result<(), Error> {
+            // check that the caller is the root user
+            let caller = Self::env().caller();
+            if self.root != caller {
+                return Err(Error::PermissionDenied)
+            }
+            self.maintenance_windows = Vec::default();
+            Ok(())
+        }
+
+
+        // 94 🟢 GET MAINTENANCE WINDOWS
+        // returns the scheduled (start, end) maintenance windows
+        #[ink(message)]
+        pub fn get_maintenance_windows(&self) -> Vec<(u64, u64)> {
+            self.maintenance_windows.clone()
+        }
+
+
+        // 95 🟢 QUEUE CLAIM [RELAYER]
+        // like claim_for, but the payout is held as a pending claim that must be
+        // finalized within the pending_timeout_ms, returns the amount held
+        // the signature is made the same way as for claim_for, with b"queue_claim" as the tag
+        #[ink(message)]
+        pub fn queue_claim(&mut self, 
+            beneficiary: AccountId,
+            ip: Vec<u8>,
+            signature: Vec<u8>,
+            nonce: u64
+        ) -> Result<Balance, Error> {
+            if self.pending_claims.contains(beneficiary) {
+                return Err(Error::ClaimPending)
+            }
+            self.verify_signed_claim(b"queue_claim", beneficiary, &ip, signature, nonce)?;
+            self.pay_claim(beneficiary, ip, None, false, true, 0)
+        }
+
+
+        // 96 🟢 FINALIZE CLAIM [ANYONE]
+        // sends a pending claim to the beneficiary before it expires,
+        // returns the amount sent (the ClaimEvent is emitted here, once coin moves)
+        #[ink(message)]
+        pub fn finalize_claim(&mut self, beneficiary: AccountId) -> Result<Balance, Error> {
+            let pending = self.pending_claims.get(beneficiary)
+                .ok_or(Error::NoPendingClaim)?;
+            let now = self.env().block_timestamp();
+            if now > pending.expires {
+                return Err(Error::ClaimExpired)
+            }
+
+            // clear the pending claim before the coin is sent
+            self.pending_claims.remove(beneficiary);
+            self.pending_total = self.pending_total.saturating_sub(pending.amount);
+            if self.env().transfer(beneficiary, pending.amount).is_err() {
+                return Err(Error::PayoutFailed)
+            }
+
+            if self.events_enabled {
+                Self::env().emit_event(ClaimEvent {
+                    timestamp: now,
+                    user_ip: pending.ip_key,
+                    pebble: beneficiary,
+                    payout: pending.amount,
+                });
+            }
+            Ok(pending.amount)
+        }
+
+
+        // 97 🟢 EXPIRE CLAIM [ANYONE]
+        // frees the coin held by a pending claim once it has expired, the claim is
+        // taken back out of the total payouts, the hourly and daily totals, the
+        // fees, the IP address total and the beneficiary's payout and claim history
+        // but the beneficiary's timer still runs from the queued claim
+        #[ink(message)]
+        pub fn expire_claim(&mut self, beneficiary: AccountId) -> Result<Balance, Error> {
+            let pending = self.pending_claims.get(beneficiary)
+                .ok_or(Error::NoPendingClaim)?;
+            if self.env().block_timestamp() <= pending.expires {
+                return Err(Error::ClaimNotExpired)
+            }
+            let amount = pending.amount;
+
+            self.pending_claims.remove(beneficiary);
+            self.pending_total = self.pending_total.saturating_sub(amount);
+            self.unrecord_payout(pending.queued_at, amount);
+            self.total_fees_collected = self.total_fees_collected.saturating_sub(pending.fee);
+
+            // only take it off the daily spend if it was queued in the current day
+            if pending.queued_at >= self.day_start {
+                self.day_spent = self.day_spent.saturating_sub(amount);
+            }
+
+            let ip_total = self.ip_total_payout.get(pending.ip_key.clone()).unwrap_or_default();
+            self.ip_total_payout.insert(pending.ip_key, &ip_total.saturating_sub(amount));
+
+            if let Some(mut user_details) = self.user_map.get(beneficiary) {
+                user_details.payout = user_details.payout.saturating_sub(amount);
+                self.user_map.insert(beneficiary, &user_details);
+            }
+
+            let mut history = self.claim_history.get(beneficiary).unwrap_or_default();
+            if let Some(index) = history.iter().rposition(|record| *record == (pending.queued_at, amount)) {
+                history.remove(index);
+                self.claim_history.insert(beneficiary, &history);
+            }
+            Ok(amount)
+        }
+
+
+        // 98 🟢 GET PENDING CLAIM
+        // returns the (amount, expiry time) of the beneficiary's pending claim, if any
+        #[ink(message)]
+        pub fn get_pending_claim(&self, beneficiary: AccountId) -> Option<(Balance, u64)> {
+            if !self.lookup_allowed(beneficiary) {
+                return None
+            }
+            self.pending_claims.get(beneficiary).map(|pending| (pending.amount, pending.expires))
+        }
+
+
+        // 99 🟢 SET PENDING TIMEOUT MS (ADMIN ONLY)
+        // lets an admin set how long a queued claim has to be finalized
+        #[ink(message)]
+        pub fn set_pending_timeout_ms(&mut self, new_pending_timeout_ms: u64) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            self.pending_timeout_ms = new_pending_timeout_ms;
+            Ok(())
+        }
+
+
+        // 100 🟢 MY STATUS [ANYONE]
+        // returns the caller's whole faucet status for this IP address in one call
+        #[ink(message)]
+        pub fn my_status(&self, my_ip_address: Vec<u8>) -> UserStatus {
+            let caller = Self::env().caller();
+            UserStatus {
+                verified: self.has_claimed(caller),
+                blocked: self.is_blocked(caller)
+                    || self.blocked_ips.get(Self::hash_ip(&my_ip_address)).unwrap_or_default(),
+                eligibility: self.eligibility_reason(my_ip_address),
+                seconds_remaining: self.seconds_until_eligible(caller),
+                last_claim: self.user_record(caller).unwrap_or_default().timestamp,
+                preview_payout: self.preview_payout(caller),
+            }
+        }
+
+
+        // 101 🟢 ELIGIBILITY FOR IPS
+        // read only check of whether the user could claim from each of the first
+        // MAX_ELIGIBILITY_IPS IP addresses given (e.g. wifi and cellular)
+        #[ink(message)]
+        pub fn eligibility_for_ips(&self, user: AccountId, ips: Vec<Vec<u8>>) -> Vec<bool> {
+            ips.into_iter()
+                .take(MAX_ELIGIBILITY_IPS)
+                .map(|ip| self.check_claim_allowed(user, ip, None, false, 0).is_ok())
+                .collect()
+        }
+
+
+        // 102 🟢 SET ROUND TO (ADMIN ONLY)
+        // lets an admin round payouts down to a clean denomination, 0 turns rounding off
+        #[ink(message)]
+        pub fn set_round_to(&mut self, new_round_to: Balance) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            if !self.within_payout_cap(new_round_to) {
+                return Err(Error::PayoutTooHigh)
+            }
+            self.round_to = new_round_to;
+            Ok(())
+        }
+
+
+        // 103 🟢 SET CLAIM FEE (ADMIN ONLY)
+        // lets an admin keep a small cut of each claim to cover gas, the fee
+        // must be less than the get_payout, the first_claim_payout (if set)
+        // and the get_payout of every extra pool
+        #[ink(message)]
+        pub fn set_claim_fee(&mut self, new_claim_fee: Balance) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            let pool_payouts_allowed = self.pool_ids.iter().all(|pool_id| {
+                Self::fee_allowed(new_claim_fee, self.pools.get(pool_id).unwrap_or_default().get_payout)
+            });
+            if !Self::fee_allowed(new_claim_fee, self.get_payout)
+            || (self.first_claim_payout > 0 && !Self::fee_allowed(new_claim_fee, self.first_claim_payout))
+            || !pool_payouts_allowed {
+                return Err(Error::FeeTooHigh)
+            }
+            self.claim_fee = new_claim_fee;
+            Ok(())
+        }
+
+
+        // 104 🟢 RECONCILE (ROOT ONLY)
+        // lets the root account count coin sent straight to the contract address
+        // (not through fund) in total_funded, returns the amount added
+        #[ink(message)]
+        pub fn reconcile(&mut self) -> Result<Balance, Error> {
+            // check that the caller is the root user
+            let caller = Self::env().caller();
+            if self.root != caller {
+                return Err(Error::PermissionDenied)
+            }
+
+            // everything paid out plus everything still held must have been funded,
+            // a queued claim is in both total_payouts and the balance so it is
+            // only counted once
+            let accounted = self.env().balance()
+                .saturating_add(self.total_payouts)
+                .saturating_sub(self.pending_total);
+            let adjustment = accounted.saturating_sub(self.total_funded);
+            self.total_funded = self.total_funded.max(accounted);
+
+            Self::env().emit_event(ReconcileEvent {
+                root: caller,
+                timestamp: self.env().block_timestamp(),
+                adjustment,
+                total_funded: self.total_funded,
+            });
+            Ok(adjustment)
+        }
+
+
+        // 105 🟢 GET COIN WITH REGION [ANYONE]
+        // like get_coin, but adds the payout to the total for a short region code
+        // for analytics, an empty region is just a normal get_coin
+        #[ink(message)]
+        pub fn get_coin_with_region(&mut self, 
+            my_ip_address: Vec<u8>,
+            region: Vec<u8>
+        ) -> Result<Balance, Error> {
+            if region.len() > MAX_REGION_LEN {
+                return Err(Error::RegionTooLong)
+            }
+            let caller = Self::env().caller();
+            let payout = self.pay_claim(caller, my_ip_address, None, false, false, 0)?;
+            if !region.is_empty() {
+                let total = self.region_totals.get(region.clone()).unwrap_or_default();
+                self.region_totals.insert(region, &total.saturating_add(payout));
+            }
+            Ok(payout)
+        }
+
+
+        // 106 🟢 REGION TOTAL
+        // returns the total coin paid out to claims tagged with this region code
+        #[ink(message)]
+        pub fn region_total(&self, region: Vec<u8>) -> Balance {
+            self.region_totals.get(region).unwrap_or_default()
+        }
+
+
+        // 107 🟢 IP USAGE
+        // returns (accounts tagged to this IP address, limit_ip_total) so a front
+        // end can show how much of the IP address limit is used
+        #[ink(message)]
+        pub fn ip_usage(&self, ip: Vec<u8>) -> (u128, u128) {
+            (self.ip_account_count(ip), self.limit_ip_total)
+        }
+
+
+        // 108 🟢 SET AUTO PAUSE THRESHOLD (ADMIN ONLY)
+        // lets an admin have the faucet pause itself when its balance drops below this, 0 turns it off
+        #[ink(message)]
+        pub fn set_auto_pause_threshold(&mut self, new_auto_pause_threshold: Balance) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            self.auto_pause_threshold = new_auto_pause_threshold;
+            Ok(())
+        }
+
+
+        // 109 🟢 SET POOL (ADMIN ONLY)
+        // lets an admin create or update an extra faucet pool with its own payout,
+        // timer and budget (0 for no budget), what the pool has paid out is kept
+        #[ink(message)]
+        pub fn set_pool(&mut self, 
+            pool_id: u32,
+            get_payout: Balance,
+            limit_timer: u64,
+            budget: Balance
+        ) -> Result<(), Error> {
+            // check that the caller is an admin
+            let caller = Self::env().caller();
+            if !self.is_admin(caller) {
+                return Err(Error::PermissionDenied)
+            }
+            // pool 0 is the main faucet, set through the regular setters
+            if pool_id == 0 {
+                return Err(Error::InvalidPool)
+            }
+            if !self.within_payout_cap(get_payout) {
+                return Err(Error::PayoutTooHigh)
+            }
+            if !Self::fee_allowed(self.claim_fee, get_payout) {
+                return Err(Error::FeeTooHigh)
+            }
+            // new pools are listed so reset_stats can find them
+            if !self.pool_ids.contains(&pool_id) {
+                if self.pool_ids.len() >= MAX_POOLS {
+                    return Err(Error::TooManyPools)
+                }
+                self.pool_ids.push(pool_id);
+            }
+            let mut pool = self.pools.get(pool_id).unwrap_or_default();
+            pool.get_payout = get_payout;
+            pool.limit_timer = limit_timer;
+            pool.budget = budget;
+            self.pools.insert(pool_id, &pool);
+            Ok(())
+        }
+
+
+        // 110 🟢 GET POOL
+        // returns the settings and accounting for an extra faucet pool, if it exists
+        #[ink(message)]
+        pub fn get_pool(&self, pool_id: u32) -> Option<PoolSettings> {
+            self.pools.get(pool_id)
+        }
+
+
+        // 111 🟢 GET COIN FROM POOL [ANYONE]
+        // lets an eligible user get coin from an extra faucet pool, each pool has
+        // its own payout, timer, budget, user records and IP address limits, every
+        // other get_coin check (pause, windows, blocklists, allowlist, daily and
+        // block limits, reserve and so on) is shared with the main faucet
+        #[ink(message)]
+        pub fn get_coin_from_pool(&mut self, 
+            pool_id: u32,
+            my_ip_address: Vec<u8>
+        ) -> Result<Balance, Error> {
+            // pool 0 is the main faucet, claimed through get_coin
+            if pool_id == 0 {
+                return Err(Error::InvalidPool)
+            }
+            let caller = Self::env().caller();
+            self.pay_claim(caller, my_ip_address, None, false, false, pool_id)
+        }
+
+
+        // 112 🟢 ACCOUNT COUNT
+        // returns how many accounts list_accounts can page over
+        #[ink(message)]
+        pub fn account_count(&self) -> u32 {
+            self.account_count
+        }
+
+        // 113 🟢 CANCEL ROOT TRANSFER (ROOT ONLY)
+        // cancels a root nomination that has not been accepted yet
+        #[ink(message)]
+        pub fn cancel_root_transfer(&mut self) -> Result<(), Error> {
+            // check that the caller is the root user
+            let caller = Self::env().caller();
+            if self.root != caller {
+                return Err(Error::PermissionDenied)
+            }
+            self.pending_root = None;
+            Ok(())
+        }
+
+
+        // 114 🟢 REGISTER SIGNER [ANYONE]
+        // sets the compressed ECDSA public key that must sign the caller's relayed
+        // claims (claim_for and queue_claim), registering again replaces it
+        #[ink(message)]
+        pub fn register_signer(&mut self, signer_key: [u8; 33]) -> Result<(), Error> {
+            let caller = Self::env().caller();
+            self.signer_keys.insert(caller, &signer_key);
+            Ok(())
+        }
+
+
+        // 115 🟢 GET SIGNER
+        // returns the ECDSA public key this account registered for relayed claims
+        #[ink(message)]
+        pub fn get_signer(&self, who: AccountId) -> Option<[u8; 33]> {
+            if !self.lookup_allowed(who) {
+                return None
+            }
+            self.signer_keys.get(who)
+        }
+
+
+        // END OF MESSAGE FUNCTIONS
+
+
+        // PRIVATE HELPER FUNCTIONS >>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>
+
+        // counts a new account in total_pebble_accounts and adds it to the end
+        // of the account index, called on every claim
+        // (accounts already in the index are left alone)
+        fn increment_accounts(&mut self, account: AccountId) {
+            if self.account_index.contains(account) {
+                return
+            }
+            self.total_pebble_accounts = self.total_pebble_accounts.saturating_add(1);
+            self.accounts.insert(self.account_count, &account);
+            self.account_index.insert(account, &self.account_count);
+            self.account_count = self.account_count.saturating_add(1);
+        }
+
+        // stops counting an account in total_pebble_accounts and takes it out of
+        // the account index, called when an account is removed from the user_map
+        // the last account is moved into the freed slot so removal touches at
+        // most two entries (list_accounts order is not kept)
+        fn decrement_accounts(&mut self, account: AccountId) {
+            let Some(index) = self.account_index.take(account) else {
+                return
+            };
+            self.total_pebble_accounts = self.total_pebble_accounts.saturating_sub(1);
+            let last = self.account_count.saturating_sub(1);
+            if index != last {
+                if let Some(last_account) = self.accounts.get(last) {
+                    self.accounts.insert(index, &last_account);
+                    self.account_index.insert(last_account, &index);
+                }
+            }
+            self.accounts.remove(last);
+            self.account_count = last;
+        }
+
+        // checks a relayed claim was signed by the beneficiary, the signature is an
+        // ECDSA signature over the Blake2x256 hash of the SCALE encoded (faucet, tag,
+        // beneficiary, ip, nonce) that must recover to the key the beneficiary
+        // registered, and the nonce must be the next one so each signature can only be used once
+        // the faucet's own address and the message tag stop a signature made for one
+        // faucet or one message from being replayed against another
+        fn verify_signed_claim(&self, 
+            tag: &[u8],
+            beneficiary: AccountId,
+            ip: &[u8],
+            signature: Vec<u8>,
+            nonce: u64
+        ) -> Result<(), Error> {
+            if nonce != self.nonce_of(beneficiary) {
+                return Err(Error::BadNonce)
+            }
+            let Some(signer_key) = self.signer_keys.get(beneficiary) else {
+                return Err(Error::SignerNotRegistered)
+            };
+            let signature: [u8; 65] = signature.try_into().map_err(|_| Error::BadSignature)?;
+            let message = ink::scale::Encode::encode(
+                &(self.env().account_id(), tag, beneficiary, ip, nonce)
+            );
+            let message_hash = self.env().hash_bytes::<Blake2x256>(&message);
+            match self.env().ecdsa_recover(&signature, &message_hash) {
+                Ok(recovered) if recovered == signer_key => Ok(()),
+                _ => Err(Error::BadSignature),
+            }
+        }
+
+        // the streak a claim at this time would give the user, a claim within
+        // twice the (pool's) limit_timer of the last one adds to the streak, otherwise it
+        // starts again at 1
+        fn next_streak(&self, user_details: &Pebble, newuser: bool, now: u64, limit_timer: u64) -> u32 {
+            let time_since = now.wrapping_sub(user_details.timestamp);
+            if !newuser && time_since <= limit_timer.saturating_mul(2) {
+                user_details.streak.saturating_add(1)
+            }
+            else {
+                1
+            }
+        }
+
+        // true if the account has ever got coin, including accounts pruned since
+        fn has_claimed(&self, who: AccountId) -> bool {
+            self.user_map.contains(who) || self.pruned_accounts.contains(who)
+        }
+
+        // the next claim nonce for an account, a pruned account's is kept in its tombstone
+        fn nonce_of(&self, who: AccountId) -> u64 {
+            self.claim_nonce.get(who)
+                .or_else(|| self.pruned_accounts.get(who).map(|(_, _, nonce)| nonce))
+                .unwrap_or_default()
+        }
+
+        // the Pebble record for an account, a pruned account only gets back
+        // its last claim time and lifetime payout
+        fn user_record(&self, who: AccountId) -> Option<Pebble> {
+            self.user_map.get(who).or_else(|| {
+                self.pruned_accounts.get(who).map(|(timestamp, payout, _)| Pebble {
+                    timestamp,
+                    pebble: who,
+                    payout,
+                    ..Pebble::default()
+                })
+            })
+        }
+
+        // the number of accounts tagged on an IP address as a u128, a failed
+        // conversion saturates so it trips the limit rather than bypassing it
+        fn tag_count(tags: &[AccountId]) -> u128 {
+            tags.len().try_into().unwrap_or(u128::MAX)
+        }
+
+        // true for the all zero AccountId, which nobody holds the key for
+        fn is_zero_address(account: AccountId) -> bool {
+            account == AccountId::from([0x0; 32])
+        }
+
+        // adds any coin sent in with this call to total_funded and emits a
+        // FundedEvent, so every funding of the faucet shows up on chain
+        fn record_funding(&mut self) {
+            let amount = self.env().transferred_value();
+            if amount > 0 {
+                self.total_funded = self.total_funded.saturating_add(amount);
+                Self::env().emit_event(FundedEvent {
+                    funder: Self::env().caller(),
+                    amount,
+                    new_balance: self.env().balance(),
+                });
+            }
+        }
+
+        // the (day_start, day_spent) for the daily limit window at this time,
+        // starting a new day once the daily window has passed (nothing is saved)
+        fn current_day(&self, now: u64) -> (u64, Balance) {
+            if now.wrapping_sub(self.day_start) >= DAY_MS {
+                (now, 0)
+            }
+            else {
+                (self.day_start, self.day_spent)
+            }
+        }
+
+        // true if a claim_fee leaves something of this payout for the user (0 means no fee)
+        fn fee_allowed(fee: Balance, payout: Balance) -> bool {
+            fee == 0 || fee < payout
+        }
+
+        // true if the payout setting is allowed under the max_payout_cap (0 means no cap)
+        fn within_payout_cap(&self, amount: Balance) -> bool {
+            self.max_payout_cap == 0 || amount <= self.max_payout_cap
+        }
+
+        // emits a SettingsChangedEvent with the current settings so integrators
+        // can refresh anything they have cached
+        fn emit_settings_changed(&self) {
+            Self::env().emit_event(SettingsChangedEvent {
+                eligible_payout: self.eligible_payout,
+                get_payout: self.get_payout,
+                first_claim_payout: self.first_claim_payout,
+                limit_timer: self.limit_timer,
+                limit_ip_total: self.limit_ip_total,
+                lifetime_cap: self.lifetime_cap,
+                daily_limit: self.daily_limit,
+            });
+        }
+
+        // calls PSP22::transfer on the reward token to send token_amount to the
+        // given account, returns true if the call went through and the token said Ok
+        fn transfer_reward_token(&self, to: AccountId) -> bool {
+            let Some(token) = self.reward_token else {
+                return false
+            };
+            let result = ink::env::call::build_call::<ink::env::DefaultEnvironment>()
+                .call(token)
+                .exec_input(
+                    ink::env::call::ExecutionInput::new(
+                        ink::env::call::Selector::new(ink::selector_bytes!("PSP22::transfer"))
+                    )
+                    .push_arg(to)
+                    .push_arg(self.token_amount)
+                    .push_arg(Vec::<u8>::new())
+                )
+                .returns::<Result<(), PSP22Error>>()
+                .try_invoke();
+            matches!(result, Ok(Ok(Ok(()))))
+        }
+
+        // true if the caller may use verify_account and the other per user lookups,
+        // always true unless restrict_verify is on (admins are always allowed)
+        fn verify_allowed(&self) -> bool {
+            let caller = Self::env().caller();
+            !self.restrict_verify
+            || self.is_admin(caller)
+            || self.verifier_allowlist.get(caller).unwrap_or_default()
+        }
+
+        // true if the caller may look up this account's data, accounts can always
+        // look up their own
+        fn lookup_allowed(&self, who: AccountId) -> bool {
+            Self::env().caller() == who || self.verify_allowed()
+        }
+
+        // adds a payout to total_payouts and to its hourly analytics bucket,
+        // each bucket holds (hour, amount) and is reused once its hour is a day old
+        fn record_payout(&mut self, now: u64, amount: Balance) {
+            // if the total ever saturates, flag that the stats are no longer exact
+            self.total_payouts = match self.total_payouts.checked_add(amount) {
+                Some(total) => total,
+                None => {
+                    self.stats_overflowed = true;
+                    Balance::MAX
+                }
+            };
+
+            if (self.payout_buckets.len() as u64) < PAYOUT_BUCKETS {
+                self.payout_buckets.resize(PAYOUT_BUCKETS as usize, (0, 0));
+            }
+            let hour = now / HOUR_MS;
+            let index = (hour % PAYOUT_BUCKETS) as usize;
+            let bucket = &mut self.payout_buckets[index];
+            if bucket.0 != hour {
+                *bucket = (hour, 0);
+            }
+            bucket.1 = bucket.1.saturating_add(amount);
+        }
+
+        // takes a payout that never went out (an expired pending claim) back out
+        // of total_payouts, and out of its hourly bucket if that hour is still kept
+        fn unrecord_payout(&mut self, at: u64, amount: Balance) {
+            self.total_payouts = self.total_payouts.saturating_sub(amount);
+            let hour = at / HOUR_MS;
+            if let Some(bucket) = self.payout_buckets.get_mut((hour % PAYOUT_BUCKETS) as usize) {
+                if bucket.0 == hour {
+                    bucket.1 = bucket.1.saturating_sub(amount);
+                }
+            }
+        }
+
+        // IP addresses are only ever stored and emitted as a Blake2x256 hash
+        // so raw addresses never end up on chain
+        fn hash_ip(ip: &[u8]) -> Vec<u8> {
+            let mut output = <Blake2x256 as HashOutput>::Type::default();
+            ink::env::hash_bytes::<Blake2x256>(ip, &mut output);
+            output.to_vec()
+        }
+
+        // the root account is always an admin, other admins are added by root
+        fn is_admin(&self, who: AccountId) -> bool {
+            who == self.root || self.admins.get(who).unwrap_or_default()
+        }
+
+        // an IP address must be non empty and no longer than MAX_IP_LEN
+        fn valid_ip(ip: &[u8]) -> bool {
+            !ip.is_empty() && ip.len() <= MAX_IP_LEN
+        }
+
+
+        // how long until this (hashed) IP address has waited out the ip_limit_timer
+        // in this pool, 0 if it has never been used to get coin there
+        fn ip_time_remaining(&self, pool_id: u32, ip_key: Vec<u8>, now: u64) -> u64 {
+            let last_claim = if pool_id == 0 {
+                self.ip_last_claim.get(ip_key)
+            }
+            else {
+                self.pool_ip_last_claim.get((pool_id, ip_key))
+            };
+            match last_claim {
+                Some(last_claim) => self.ip_limit_timer.saturating_sub(now.wrapping_sub(last_claim)),
+                None => 0,
+            }
+        }
+
+
+        // the settings for a pool, pool 0 is the main faucet and uses the regular
+        // get_payout and limit_timer with no budget
+        fn pool_settings(&self, pool_id: u32) -> Result<PoolSettings, Error> {
+            if pool_id == 0 {
+                return Ok(PoolSettings {
+                    get_payout: self.get_payout,
+                    limit_timer: self.limit_timer,
+                    ..PoolSettings::default()
+                })
+            }
+            self.pools.get(pool_id).ok_or(Error::InvalidPool)
+        }
+
+        // (true if the user never claimed from the pool, the user's record in the pool)
+        fn pool_user(&self, pool_id: u32, user: AccountId) -> (bool, Pebble) {
+            if pool_id == 0 {
+                return (!self.has_claimed(user), self.user_record(user).unwrap_or_default())
+            }
+            match self.pool_users.get((pool_id, user)) {
+                Some(user_details) => (false, user_details),
+                None => (true, Pebble::default()),
+            }
+        }
+
+        // the accounts tagged to this (hashed) IP address in the pool
+        fn pool_ip_tags(&self, pool_id: u32, ip_key: &[u8]) -> Vec<AccountId> {
+            if pool_id == 0 {
+                self.ipaddress_count.get(ip_key.to_vec())
+            }
+            else {
+                self.pool_ip_accounts.get((pool_id, ip_key.to_vec()))
+            }
+            .unwrap_or_default()
+        }
+
+        // the amount a claim pays, new users get the first_claim_payout if one is set,
+        // accounts that referred others get the referral bonus on top, with decay
+        // enabled the payout shrinks with the share of the initial_funded balance that
+        // is left, claims that keep a streak going get the streak_bonus on top,
+        // the claim_fee is taken off, then it is rounded to round_to units if set
+        // and a payout under min_transfer is raised to it when min_transfer_bump is on
+        // extra pools pay their own get_payout and have no first claim payout
+        // returns (the amount sent to the user, the fee kept by the contract)
+        fn claim_payout(&self, 
+            user: AccountId,
+            newuser: bool,
+            streak: u32,
+            pool_id: u32
+        ) -> (Balance, Balance) {
+            let mut base = if pool_id > 0 {
+                self.pools.get(pool_id).unwrap_or_default().get_payout
+            }
+            else if newuser && self.first_claim_payout > 0 {
+                self.first_claim_payout
+            }
+            else {
+                self.get_payout
+            };
+
+            // base * (1 + referral_count * referral_multiplier_bps / 10000), capped
+            let referrals = self.referral_counts.get(user).unwrap_or_default();
+            if self.referral_multiplier_bps > 0 && referrals > 0 {
+                let multiplier_bps = referrals
+                    .saturating_mul(self.referral_multiplier_bps.into())
+                    .saturating_add(10_000)
+                    .min(MAX_REFERRAL_MULTIPLIER_BPS);
+                base = base.saturating_mul(multiplier_bps) / 10_000;
+            }
+
+            let mut payout = if !self.decay_enabled || self.initial_funded == 0 {
+                base
+            }
+            else {
+                let balance = self.env().balance().min(self.initial_funded);
+                base.saturating_mul(balance) / self.initial_funded
+            };
+
+            // claims that keep a streak going get the streak bonus on top
+            if streak > 1 {
+                payout = payout.saturating_add(self.streak_bonus);
+            }
+
+            // the claim fee stays in the contract, the user gets the rest
+            let fee = self.claim_fee.min(payout);
+            payout = payout.saturating_sub(fee);
+
+            // a nonzero round_to rounds the net payout down to a whole number of
+            // round_to units, but never a nonzero payout down to zero (a zero
+            // payout stays zero)
+            if let Some(units) = payout.checked_div(self.round_to) {
+                if payout > 0 {
+                    payout = units.saturating_mul(self.round_to).max(self.round_to);
+                }
+            }
+
+            // with min_transfer_bump on, small payouts are raised to min_transfer
+            // (a payout the fee took all of is left at zero)
+            if self.min_transfer_bump && payout > 0 && payout < self.min_transfer {
+                payout = self.min_transfer;
+            }
+            (payout, fee)
+        }
+
+
+        // the full guard chain for a claim by this user from this IP address,
+        // shared by get_coin and the read only checks
+        // skip_limits (a valid bypass code) skips the timers and IP address limits
+        // pool_id picks the pool whose payout, timer, budget, user records and IP
+        // address limits apply, 0 is the main faucet
+        fn check_claim_allowed(&self, 
+            user: AccountId,
+            my_ip_address: Vec<u8>,
+            referrer: Option<AccountId>,
+            skip_limits: bool,
+            pool_id: u32
+        ) -> Result<ClaimPlan, Error> {
+            // reject all claims while the faucet is paused
+            if self.paused {
+                return Err(Error::Paused);
+            }
+
+            let pool = self.pool_settings(pool_id)?;
+
+            // reject empty or oversized IP addresses
+            if !Self::valid_ip(&my_ip_address) {
+                return Err(Error::InvalidIpAddress);
+            }
+
+            let now = self.env().block_timestamp();
+
+            // timed campaigns only pay between open_time and close_time (0 is unbounded)
+            if (self.open_time > 0 && now < self.open_time)
+            || (self.close_time > 0 && now > self.close_time) {
+                return Err(Error::FaucetClosed);
+            }
+
+            // no claims during a scheduled maintenance window (start and end included)
+            if self.maintenance_windows.iter().any(|&(start, end)| now >= start && now <= end) {
+                return Err(Error::FaucetClosed);
+            }
+
+            // reject blocked accounts
+            if self.is_blocked(user) {
+                return Err(Error::AccountBlocked);
+            }
+
+            // reject blocked IP addresses before doing any heavier work
+            let ip_key = Self::hash_ip(&my_ip_address);
+            if self.blocked_ips.get(ip_key.clone()).unwrap_or_default() {
+                return Err(Error::IpBlocked);
+            }
+
+            // allowlist only faucets pay allowlisted accounts only
+            if self.allowlist_only && !self.allowlist.get(user).unwrap_or_default() {
+                return Err(Error::NotAllowlisted);
+            }
+
+            let (newuser, user_details) = self.pool_user(pool_id, user);
+
+            // a nonzero min_account_age_ms makes new accounts check_in and then wait
+            // that long before their first claim, accounts that claimed before
+            // first_seen existed count as old enough
+            if self.min_account_age_ms > 0 {
+                let old_enough = match self.first_seen.get(user) {
+                    Some(first_seen) => now.saturating_sub(first_seen) >= self.min_account_age_ms,
+                    None => self.has_claimed(user),
+                };
+                if !old_enough {
+                    return Err(Error::AccountTooNew);
+                }
+            }
+
+            let time_since = now.wrapping_sub(user_details.timestamp);
+            let ip_tags = self.pool_ip_tags(pool_id, &ip_key);
+            let ip_tags_len = Self::tag_count(&ip_tags);
+            let streak = self.next_streak(&user_details, newuser, now, pool.limit_timer);
+            let (payout, fee) = self.claim_payout(user, newuser, streak, pool_id);
+
+            // a claim the fee would take all of pays nothing, so it is rejected
+            if fee > 0 && payout == 0 {
+                return Err(Error::FeeTooHigh);
+            }
+
+            // a payout under the chain's existential deposit can fail or reap
+            // a new account, so it is rejected unless min_transfer_bump raised it
+            if payout < self.min_transfer {
+                return Err(Error::PayoutBelowMinimum);
+            }
+
+            // a nonzero max_accounts stops new accounts once the faucet is full,
+            // existing accounts can still reclaim (this counts the account index)
+            if self.max_accounts > 0 && !self.account_index.contains(user)
+            && u128::from(self.account_count) >= self.max_accounts {
+                return Err(Error::MaxAccountsReached);
+            }
+
+            // a nonzero lifetime cap limits the total coin any one account can get
+            if self.lifetime_cap > 0
+            && user_details.payout.saturating_add(payout) > self.lifetime_cap {
+                return Err(Error::LifetimeCapReached);
+            }
+
+            // with one_account_per_ip on, only the account already tagged to
+            // this IP address can claim from it, whatever limit_ip_total is
+            if !skip_limits && self.one_account_per_ip
+            && !ip_tags.is_empty() && !ip_tags.contains(&user) {
+                return Err(Error::IpAlreadyUsed);
+            }
+
+            // the IP address list can never grow past MAX_IP_VEC_LEN, even with
+            // a bypass code or a misconfigured limit_ip_total
+            if ip_tags.len() >= MAX_IP_VEC_LEN && !ip_tags.contains(&user) {
+                return Err(Error::IpLimitReached);
+            }
+
+            // the IP address must have < the limit of total IP tags
+            // unless the user is already tagged to it
+            if !skip_limits && ip_tags_len >= self.limit_ip_total && !ip_tags.contains(&user) {
+                return Err(Error::IpLimitReached);
+            }
+
+            // a nonzero max_ips_per_account limits how many distinct IP addresses
+            // one account can claim from
+            let ips_seen = self.ips_seen.get(user).unwrap_or_default();
+            let ips_seen_len: u128 = ips_seen.len().try_into().unwrap_or(u128::MAX);
+            if self.max_ips_per_account > 0 && !ips_seen.contains(&ip_key)
+            && ips_seen_len >= self.max_ips_per_account {
+                return Err(Error::TooManyIps);
+            }
+
+            // EITHER this is the user's first claim (and new accounts skip the timer) OR...
+            // the user has claimed before but it has been long enough
+            // (an existing account whose payout is zero still waits out the timer)
+            let bypass_timer = skip_limits || (newuser && self.new_account_bypass_timer);
+            if time_since < pool.limit_timer && !bypass_timer {
+                return Err(Error::RateLimited);
+            }
+
+            // the IP address must have waited out the ip_limit_timer
+            if !skip_limits && self.ip_time_remaining(pool_id, ip_key.clone(), now) > 0 {
+                return Err(Error::IpRateLimited);
+            }
+
+            // the referrer is only paid on a new user's first claim
+            let referrer = if newuser { referrer } else { None };
+            let mut required = payout;
+            if referrer.is_some() {
+                required = required.saturating_add(self.referrer_payout);
+            }
+
+            // a nonzero pool budget caps the total coin the pool pays out
+            if pool.budget > 0 && pool.paid_out.saturating_add(payout) > pool.budget {
+                return Err(Error::PoolBudgetReached);
+            }
+
+            // start a new day once the daily window has passed
+            let (day_start, day_spent) = self.current_day(now);
+
+            // a nonzero daily limit caps the total coin paid out each day
+            if self.daily_limit > 0
+            && day_spent.saturating_add(required) > self.daily_limit {
+                return Err(Error::DailyLimitReached);
+            }
+
+            // a nonzero max_claims_per_block caps the claims paid in one block
+            let block = self.env().block_number();
+            let mut claims_this_block = self.claims_this_block;
+            if block != self.last_block {
+                claims_this_block = 0;
+            }
+            if self.max_claims_per_block > 0
+            && claims_this_block >= self.max_claims_per_block {
+                return Err(Error::BlockRateLimited);
+            }
+
+            // make sure the contract has enough balance to pay out and still
+            // keep its reserve, otherwise fail before any storage is changed
+            // or events are emitted
+            // coin held for pending claims is not available either
+            if self.env().balance()
+            < required.saturating_add(self.reserve).saturating_add(self.pending_total) {
+                return Err(Error::ContractDrained);
+            }
+
+            Ok(ClaimPlan {
+                now,
+                ip_key,
+                user_details,
+                ip_tags,
+                payout,
+                referrer,
+                required,
+                day_start,
+                day_spent,
+                block,
+                claims_this_block,
+                streak,
+                fee,
+            })
+        }
+
+
+        // shared payout path for every get_coin message, the claim is made
+        // by and paid to the beneficiary, returns the amount transferred
+        // hold queues the payout as a pending claim instead of sending it
+        // pool_id picks the pool the claim is made from, 0 is the main faucet
+        fn pay_claim(&mut self, 
+            beneficiary: AccountId,
+            my_ip_address: Vec<u8>,
+            referrer: Option<AccountId>,
+            skip_limits: bool,
+            hold: bool,
+            pool_id: u32
+        ) -> Result<Balance, Error> {
+            let ClaimPlan {
+                now,
+                ip_key,
+                mut user_details,
+                mut ip_tags,
+                payout,
+                referrer,
+                required,
+                day_start,
+                day_spent,
+                block,
+                claims_this_block,
+                streak,
+                fee,
+            } = self.check_claim_allowed(beneficiary, my_ip_address, referrer, skip_limits, pool_id)?;
+
+            // all storage is updated before any coin is transferred so that
+            // a re-entrant call sees this claim as already made
+            
+            // update the user details (timestamp updated on get coin only)
+            user_details.payout = user_details.payout.saturating_add(payout);
+            user_details.ip_address = ip_key.clone();
+            user_details.pebble = beneficiary;
+            user_details.timestamp = now;
+            user_details.streak = streak;
+            if referrer.is_some() {
+                user_details.referrer = referrer;
+            }
+            
+            // read the claim nonce before a pruned account's tombstone is removed
+            let nonce = self.nonce_of(beneficiary);
+
+            // update the user_map (or the pool's user records), a pruned account
+            // coming back is listed again
+            if pool_id == 0 {
+                self.user_map.insert(beneficiary, &user_details);
+                self.pruned_accounts.remove(beneficiary);
+            }
+            else {
+                self.pool_users.insert((pool_id, beneficiary), &user_details);
+            }
+
+            // remember when the beneficiary was first seen if they never checked in
+            if !self.first_seen.contains(beneficiary) {
+                self.first_seen.insert(beneficiary, &now);
+            }
+
+            // add this claim to the beneficiary's history, dropping the oldest when full
+            let mut history = self.claim_history.get(beneficiary).unwrap_or_default();
+            if history.len() >= MAX_CLAIM_HISTORY {
+                history.remove(0);
+            }
+            history.push((now, payout));
+            self.claim_history.insert(beneficiary, &history);
+
+            // remember the IP addresses this beneficiary has claimed from
+            let mut ips_seen = self.ips_seen.get(beneficiary).unwrap_or_default();
+            if !ips_seen.contains(&ip_key) {
+                ips_seen.push(ip_key.clone());
+                self.ips_seen.insert(beneficiary, &ips_seen);
+            }
+
+            // bump the beneficiary's claim nonce
+            self.claim_nonce.insert(beneficiary, &nonce.saturating_add(1));
+
+            // update the last claim time for this IP address
+            if pool_id == 0 {
+                self.ip_last_claim.insert(ip_key.clone(), &now);
+            }
+            else {
+                self.pool_ip_last_claim.insert((pool_id, ip_key.clone()), &now);
+            }
+
+            // add the payout to the total paid to this IP address
+            let ip_total = self.ip_total_payout.get(ip_key.clone()).unwrap_or_default();
+            self.ip_total_payout.insert(ip_key.clone(), &ip_total.saturating_add(payout));
+
+            // update the ip address count 
+            if ip_tags.contains(&beneficiary) {
+                // do nothing
+            }
+            else if pool_id == 0 {
+                // count the IP address if this is the first time it has been seen
+                if !self.ipaddress_count.contains(ip_key.clone()) {
+                    self.total_unique_ips = self.total_unique_ips.saturating_add(1);
+                }
+                // add the beneficiary and update the map
+                ip_tags.push(beneficiary);
+                self.ipaddress_count.insert(ip_key.clone(), &ip_tags);
+            }
+            else {
+                ip_tags.push(beneficiary);
+                self.pool_ip_accounts.insert((pool_id, ip_key.clone()), &ip_tags);
+            }
+
+            // update total total_payouts
+            self.record_payout(now, payout);
+            self.total_fees_collected = self.total_fees_collected.saturating_add(fee);
+
+            // update the daily spend
+            self.day_start = day_start;
+            self.day_spent = day_spent.saturating_add(required);
+
+            // update the claims made in this block
+            self.last_block = block;
+            self.claims_this_block = claims_this_block.saturating_add(1);
+
+            // update the total_pebble_accounts IF this account was not listed yet,
+            // only the main faucet's accounts are counted and listed, pool users
+            // are kept in the pool's own records
+            if pool_id == 0 {
+                self.increment_accounts(beneficiary);
+            }
+
+            // add the claim to the pool's accounting
+            if let Some(mut pool) = self.pools.get(pool_id) {
+                pool.paid_out = pool.paid_out.saturating_add(payout);
+                pool.claims = pool.claims.saturating_add(1);
+                self.pools.insert(pool_id, &pool);
+            }
+
+            // credit the referrer with the new friend
+            if let Some(referrer) = referrer {
+                let count = self.referral_counts.get(referrer).unwrap_or_default();
+                self.referral_counts.insert(referrer, &count.saturating_add(1));
+                self.record_payout(now, self.referrer_payout);
+            }
+
+            if hold {
+                // a queued claim holds the payout until finalize_claim or expire_claim
+                let expires = now.saturating_add(self.pending_timeout_ms);
+                self.pending_claims.insert(beneficiary, &PendingClaim {
+                    amount: payout,
+                    fee,
+                    expires,
+                    queued_at: now,
+                    ip_key: ip_key.clone(),
+                });
+                self.pending_total = self.pending_total.saturating_add(payout);
+            }
+            // payout the claim amount to the beneficiary
+            // (an error here reverts all the storage changes above, so it cannot
+            // be counted in total_failed_payouts, the caller gets PayoutFailed)
+            else if self.env().transfer(beneficiary, payout).is_err() {
+                return Err(Error::PayoutFailed);
+            }
+
+            // payout the referrer_payout to the referrer
+            if let Some(referrer) = referrer {
+                if self.env().transfer(referrer, self.referrer_payout).is_err() {
+                    return Err(Error::PayoutFailed);
+                }
+            }
+
+            // send the reward token too if one is set, a failure only reverts
+            // the claim when token_strict is on, otherwise it is counted
+            // (queued claims do not get the reward token)
+            if !hold && self.reward_token.is_some() && !self.transfer_reward_token(beneficiary) {
+                if self.token_strict {
+                    return Err(Error::TokenTransferFailed);
+                }
+                self.total_failed_token_transfers = self.total_failed_token_transfers.saturating_add(1);
+            }
+            
+            // emit events for the claim unless they have been turned off to save gas
+            if self.events_enabled && pool_id > 0 {
+                Self::env().emit_event(PoolClaimEvent {
+                    pool_id,
+                    pebble: beneficiary,
+                    timestamp: now,
+                    payout,
+                });
+            }
+            // a queued claim gets its ClaimEvent from finalize_claim
+            else if self.events_enabled && !hold {
+                Self::env().emit_event(ClaimEvent {
+                    timestamp: now,
+                    user_ip: ip_key.clone(),
+                    pebble: beneficiary,
+                    payout,
+                });
+
+                // emit event for the referral payout
+                if let Some(referrer) = referrer {
+                    Self::env().emit_event(PayoutEvent {
+                        timestamp: now,
+                        user_ip: ip_key,
+                        pebble: referrer,
+                        payout: self.referrer_payout,
+                    });
+                }
+            }
+
+            // pause the faucet once the balance drops below the auto_pause_threshold
+            // rather than dribble out the rest, an admin can unpause after refunding
+            let balance = self.env().balance();
+            if self.auto_pause_threshold > 0 && balance < self.auto_pause_threshold {
+                self.paused = true;
+                Self::env().emit_event(AutoPausedEvent {
+                    timestamp: now,
+                    balance,
+                    threshold: self.auto_pause_threshold,
+                });
+            }
+
+            Ok(payout)
+        }
+
+
+    }
+    // END OF CONTRACT LOGIC
+
+
+    // UNIT TESTS >>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ink::env::test;
+        use ink::env::DefaultEnvironment as Env;
+
+        const IP: &[u8] = b"10.0.0.1";
+
+        // the address the faucet is deployed at in these tests
+        fn contract_id() -> AccountId {
+            AccountId::from([0xFA; 32])
+        }
+
+        fn accounts() -> test::DefaultAccounts<Env> {
+            test::default_accounts::<Env>()
+        }
+
+        fn set_caller(who: AccountId) {
+            test::set_caller::<Env>(who);
+        }
+
+        fn balance_of(who: AccountId) -> Balance {
+            test::get_account_balance::<Env>(who).unwrap_or_default()
+        }
+
+        fn set_contract_balance(amount: Balance) {
+            test::set_account_balance::<Env>(contract_id(), amount);
+        }
+
+        // the events of one type emitted so far, oldest first
+        fn events_of<E: ink::env::Event + ink::scale::Decode>() -> Vec<E> {
+            test::recorded_events()
+                .filter(|event| event.topics.first().map(|topic| topic.as_slice())
+                    == E::SIGNATURE_TOPIC.as_ref().map(|topic| topic.as_slice()))
+                .map(|event| E::decode(&mut &event.data[..]).unwrap())
+                .collect()
+        }
+
+        fn set_now(timestamp: u64) {
+            test::set_block_timestamp::<Env>(timestamp);
+        }
+
+        // a deterministic ECDSA key for the account [seed; 32], registered
+        // with the faucet as that account's signer (the caller is left unchanged)
+        fn signer(faucet: &mut ContractStorage, seed: u8) -> (secp256k1::SecretKey, AccountId) {
+            let secret_key = secp256k1::SecretKey::from_slice(&[seed; 32]).unwrap();
+            let account = AccountId::from([seed; 32]);
+            let caller = ink::env::caller::<Env>();
+            set_caller(account);
+            let public_key = secp256k1::PublicKey::from_secret_key(secp256k1::SECP256K1, &secret_key);
+            faucet.register_signer(public_key.serialize()).unwrap();
+            set_caller(caller);
+            (secret_key, account)
+        }
+
+        // signs a relayed claim the way verify_signed_claim expects
+        fn sign_claim(
+            secret_key: &secp256k1::SecretKey,
+            faucet: AccountId,
+            tag: &[u8],
+            beneficiary: AccountId,
+            ip: &[u8],
+            nonce: u64
+        ) -> Vec<u8> {
+            let message = ink::scale::Encode::encode(&(faucet, tag, beneficiary, ip, nonce));
+            let mut message_hash = <Blake2x256 as HashOutput>::Type::default();
+            ink::env::hash_bytes::<Blake2x256>(&message, &mut message_hash);
+            let (recovery_id, signature) = secp256k1::SECP256K1
+                .sign_ecdsa_recoverable(&secp256k1::Message::from_digest(message_hash), secret_key)
+                .serialize_compact();
+            let mut signature = signature.to_vec();
+            signature.push(recovery_id.to_i32() as u8);
+            signature
+        }
+
+        // deploys a faucet with alice as root holding funds, paying 100 per
+        // claim with a limit_timer of 1000 ms and up to 5 accounts per IP address
+        fn faucet(funds: Balance) -> ContractStorage {
+            test::set_callee::<Env>(contract_id());
+            set_contract_balance(funds);
+            set_caller(accounts().alice);
+            let mut faucet = ContractStorage::new(accounts().alice).unwrap();
+            faucet.set_payouts_and_fund(100, 100, 0, 1000, 5, 0, 0).unwrap();
+            faucet
+        }
+
+        #[ink::test]
+        fn underfunded_get_coin_records_nothing() {
+            let mut faucet = faucet(50);
+            set_caller(accounts().bob);
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::ContractDrained));
+            assert_eq!(faucet.get_user_details(accounts().bob), None);
+            assert_eq!(faucet.get_stats_and_settings().total_payouts, 0);
+            assert_eq!(faucet.ip_account_count(IP.to_vec()), 0);
+            assert_eq!(balance_of(contract_id()), 50);
+        }
+
+        #[ink::test]
+        fn check_eligibility_moves_no_funds() {
+            let faucet = faucet(1_000);
+            let events_before = test::recorded_events().count();
+            set_caller(accounts().bob);
+            let bob_before = balance_of(accounts().bob);
+            assert_eq!(faucet.check_eligibility(IP.to_vec()), Ok(true));
+            assert_eq!(balance_of(accounts().bob), bob_before);
+            assert_eq!(balance_of(contract_id()), 1_000);
+            assert_eq!(faucet.get_user_details(accounts().bob), None);
+            assert_eq!(test::recorded_events().count(), events_before);
+        }
+
+        #[ink::test]
+        fn exact_balance_pays_out() {
+            let mut faucet = faucet(100);
+            set_caller(accounts().bob);
+            let bob_before = balance_of(accounts().bob);
+            assert_eq!(faucet.get_coin(IP.to_vec()).map(|receipt| receipt.amount), Ok(100));
+            assert_eq!(balance_of(accounts().bob), bob_before + 100);
+            assert_eq!(balance_of(contract_id()), 0);
+        }
+
+        #[ink::test]
+        fn paused_faucet_rejects_claims_until_unpaused() {
+            let mut faucet = faucet(1_000);
+            faucet.set_paused(true).unwrap();
+            assert!(faucet.get_stats_and_settings().paused);
+
+            set_caller(accounts().bob);
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::Paused));
+            assert_eq!(faucet.set_paused(false), Err(Error::PermissionDenied));
+
+            set_caller(accounts().alice);
+            faucet.set_paused(false).unwrap();
+            set_caller(accounts().bob);
+            assert!(faucet.get_coin(IP.to_vec()).is_ok());
+        }
+
+        #[ink::test]
+        fn verify_account_returns_bool() {
+            let mut faucet = faucet(1_000);
+            assert!(!faucet.verify_account(accounts().bob));
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            assert!(faucet.verify_account(accounts().bob));
+        }
+
+        #[ink::test]
+        fn root_change_emits_old_and_new_root() {
+            let mut faucet = faucet(0);
+            faucet.set_root_account(accounts().alice).unwrap();
+            assert!(events_of::<RootChangedEvent>().is_empty());
+
+            faucet.set_root_account(accounts().bob).unwrap();
+            let events = events_of::<RootChangedEvent>();
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].old_root, accounts().alice);
+            assert_eq!(events[0].new_root, accounts().bob);
+        }
+
+        #[ink::test]
+        fn root_handoff_needs_the_pending_account() {
+            let mut faucet = faucet(0);
+            faucet.transfer_root(accounts().bob).unwrap();
+
+            set_caller(accounts().charlie);
+            assert_eq!(faucet.accept_root(), Err(Error::PermissionDenied));
+            set_caller(accounts().bob);
+            faucet.accept_root().unwrap();
+            assert_eq!(faucet.get_root(), accounts().bob);
+            assert_eq!(faucet.accept_root(), Err(Error::PermissionDenied));
+        }
+
+        #[ink::test]
+        fn root_nomination_can_be_cancelled() {
+            let mut faucet = faucet(0);
+            faucet.transfer_root(accounts().bob).unwrap();
+            set_caller(accounts().bob);
+            assert_eq!(faucet.cancel_root_transfer(), Err(Error::PermissionDenied));
+            set_caller(accounts().alice);
+            faucet.cancel_root_transfer().unwrap();
+            set_caller(accounts().bob);
+            assert_eq!(faucet.accept_root(), Err(Error::PermissionDenied));
+        }
+
+        #[ink::test]
+        fn root_change_clears_the_nomination() {
+            let mut faucet = faucet(0);
+            faucet.transfer_root(accounts().bob).unwrap();
+            faucet.set_root_account(accounts().charlie).unwrap();
+            set_caller(accounts().bob);
+            assert_eq!(faucet.accept_root(), Err(Error::PermissionDenied));
+            assert_eq!(faucet.get_root(), accounts().charlie);
+        }
+
+        #[ink::test]
+        fn user_details_present_and_absent() {
+            let mut faucet = faucet(1_000);
+            assert_eq!(faucet.get_user_details(accounts().bob), None);
+
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            let user_details = faucet.get_user_details(accounts().bob).unwrap();
+            assert_eq!(user_details.pebble, accounts().bob);
+            assert_eq!(user_details.payout, 100);
+            assert_eq!(user_details.ip_address, ContractStorage::hash_ip(IP));
+        }
+
+        #[ink::test]
+        fn lifetime_cap_is_exhausted_over_claims() {
+            let mut faucet = faucet(1_000);
+            faucet.set_payouts_and_fund(100, 100, 0, 1000, 5, 200, 0).unwrap();
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            set_now(1_000);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            set_now(2_000);
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::LifetimeCapReached));
+            assert_eq!(faucet.get_user_details(accounts().bob).unwrap().payout, 200);
+        }
+
+        #[ink::test]
+        fn blocked_account_cannot_claim() {
+            let mut faucet = faucet(1_000);
+            faucet.block_account(accounts().bob).unwrap();
+            assert!(faucet.is_blocked(accounts().bob));
+
+            set_caller(accounts().bob);
+            assert!(!faucet.is_eligible(IP.to_vec()));
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::AccountBlocked));
+
+            set_caller(accounts().alice);
+            faucet.unblock_account(accounts().bob).unwrap();
+            set_caller(accounts().bob);
+            assert!(faucet.get_coin(IP.to_vec()).is_ok());
+        }
+
+        #[ink::test]
+        fn countdown_decreases_as_time_passes() {
+            let mut faucet = faucet(1_000);
+            assert_eq!(faucet.seconds_until_eligible(accounts().bob), 0);
+
+            set_now(5_000);
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            assert_eq!(faucet.seconds_until_eligible(accounts().bob), 1_000);
+            set_now(5_400);
+            assert_eq!(faucet.seconds_until_eligible(accounts().bob), 600);
+            set_now(6_000);
+            assert_eq!(faucet.seconds_until_eligible(accounts().bob), 0);
+        }
+
+        #[ink::test]
+        fn unique_ips_counts_each_ip_once() {
+            let mut faucet = faucet(1_000);
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            set_caller(accounts().charlie);
+            faucet.get_coin(b"10.0.0.2".to_vec()).unwrap();
+            set_caller(accounts().django);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            assert_eq!(faucet.get_stats_and_settings().total_unique_ips, 2);
+        }
+
+        #[ink::test]
+        fn daily_limit_resets_after_a_day() {
+            let mut faucet = faucet(1_000);
+            faucet.set_payouts_and_fund(100, 100, 0, 1000, 5, 0, 200).unwrap();
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            set_caller(accounts().charlie);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            set_caller(accounts().django);
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::DailyLimitReached));
+
+            set_now(DAY_MS - 1);
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::DailyLimitReached));
+            set_now(DAY_MS);
+            assert!(faucet.get_coin(IP.to_vec()).is_ok());
+            assert_eq!(faucet.get_stats_and_settings().day_spent, 100);
+        }
+
+        #[ink::test]
+        fn first_claim_pays_more_than_repeat_claims() {
+            let mut faucet = faucet(1_000);
+            faucet.set_payouts_and_fund(100, 100, 250, 1000, 5, 0, 0).unwrap();
+            set_caller(accounts().bob);
+            let bob_before = balance_of(accounts().bob);
+            assert_eq!(faucet.get_coin(IP.to_vec()).unwrap().amount, 250);
+            set_now(1_000);
+            assert_eq!(faucet.get_coin(IP.to_vec()).unwrap().amount, 100);
+            assert_eq!(balance_of(accounts().bob), bob_before + 350);
+        }
+
+        #[ink::test]
+        fn claims_and_batch_payouts_emit_different_events() {
+            let mut faucet = faucet(1_000);
+            set_caller(accounts().bob);
+            faucet.check_eligibility(IP.to_vec()).unwrap();
+            assert!(events_of::<ClaimEvent>().is_empty());
+
+            faucet.get_coin(IP.to_vec()).unwrap();
+            let claims = events_of::<ClaimEvent>();
+            assert_eq!(claims.len(), 1);
+            assert_eq!(claims[0].pebble, accounts().bob);
+            assert_eq!(claims[0].payout, 100);
+            assert_eq!(claims[0].user_ip, ContractStorage::hash_ip(IP));
+            assert!(events_of::<PayoutEvent>().is_empty());
+
+            set_caller(accounts().alice);
+            faucet.batch_payout(vec![accounts().charlie], 10).unwrap();
+            assert_eq!(events_of::<ClaimEvent>().len(), 1);
+            assert_eq!(events_of::<PayoutEvent>()[0].pebble, accounts().charlie);
+        }
+
+        #[ink::test]
+        fn list_accounts_pages_over_the_account_index() {
+            let mut faucet = faucet(1_000);
+            let users = [accounts().bob, accounts().charlie, accounts().django];
+            for user in users {
+                set_caller(user);
+                faucet.get_coin(IP.to_vec()).unwrap();
+            }
+            assert_eq!(faucet.account_count(), 3);
+            assert_eq!(faucet.list_accounts(0, 2), vec![users[0], users[1]]);
+            assert_eq!(faucet.list_accounts(2, 2), vec![users[2]]);
+            assert_eq!(faucet.list_accounts(3, 2), Vec::<AccountId>::new());
+            assert_eq!(faucet.list_accounts(u32::MAX, u32::MAX), Vec::<AccountId>::new());
+            assert_eq!(faucet.list_accounts(0, u32::MAX).len(), 3);
+        }
+
+        #[ink::test]
+        fn pruning_moves_the_last_account_into_the_gap() {
+            let mut faucet = faucet(1_000);
+            let users = [accounts().bob, accounts().charlie, accounts().django];
+            for user in users {
+                set_caller(user);
+                faucet.get_coin(IP.to_vec()).unwrap();
+            }
+            set_caller(accounts().alice);
+            set_now(10_000);
+            assert_eq!(faucet.prune_stale(vec![accounts().bob], 1_000), Ok(1));
+            assert_eq!(faucet.account_count(), 2);
+            assert_eq!(faucet.list_accounts(0, 10), vec![accounts().django, accounts().charlie]);
+        }
+
+        #[ink::test]
+        fn two_accounts_on_one_ip() {
+            let mut faucet = faucet(1_000);
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            set_caller(accounts().charlie);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            assert_eq!(faucet.accounts_for_ip(IP.to_vec()), vec![accounts().bob, accounts().charlie]);
+            assert_eq!(faucet.ip_account_count(IP.to_vec()), 2);
+            assert_eq!(faucet.ip_account_count(b"10.0.0.2".to_vec()), 0);
+        }
+
+        #[ink::test]
+        fn empty_and_oversized_ips_are_rejected() {
+            let mut faucet = faucet(1_000);
+            set_caller(accounts().bob);
+            let oversized = vec![b'1'; MAX_IP_LEN + 1];
+            assert_eq!(faucet.get_coin(Vec::new()), Err(Error::InvalidIpAddress));
+            assert_eq!(faucet.get_coin(oversized.clone()), Err(Error::InvalidIpAddress));
+            assert_eq!(faucet.eligibility_reason(oversized), EligibilityStatus::InvalidIpAddress);
+            assert!(faucet.get_coin(vec![b'1'; MAX_IP_LEN]).is_ok());
+        }
+
+        #[ink::test]
+        fn sweep_moves_the_whole_balance() {
+            let mut faucet = faucet(1_000);
+            let charlie_before = balance_of(accounts().charlie);
+            assert_eq!(faucet.sweep_all(accounts().charlie), Ok(1_000));
+            assert_eq!(balance_of(accounts().charlie), charlie_before + 1_000);
+            assert_eq!(balance_of(contract_id()), 0);
+            assert_eq!(events_of::<WithdrawEvent>()[0].amount, 1_000);
+
+            // an empty contract sweeps nothing
+            assert_eq!(faucet.sweep_all(accounts().charlie), Ok(0));
+
+            set_caller(accounts().bob);
+            assert_eq!(faucet.sweep_all(accounts().bob), Err(Error::PermissionDenied));
+        }
+
+        #[ink::test]
+        fn non_root_cannot_take_root() {
+            let mut faucet = faucet(0);
+            assert!(faucet.is_root(accounts().alice));
+            set_caller(accounts().bob);
+            assert_eq!(faucet.set_root_account(accounts().bob), Err(Error::PermissionDenied));
+            assert_eq!(faucet.transfer_root(accounts().bob), Err(Error::PermissionDenied));
+            assert_eq!(faucet.get_root(), accounts().alice);
+        }
+
+        #[ink::test]
+        fn allowlist_mode_pays_allowlisted_accounts_only() {
+            let mut faucet = faucet(1_000);
+            // allowlist off: anyone can claim
+            set_caller(accounts().bob);
+            assert!(faucet.get_coin(IP.to_vec()).is_ok());
+
+            set_caller(accounts().alice);
+            faucet.set_allowlist_only(true).unwrap();
+            faucet.allow_account(accounts().charlie).unwrap();
+            set_caller(accounts().django);
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::NotAllowlisted));
+            set_caller(accounts().charlie);
+            assert!(faucet.get_coin(IP.to_vec()).is_ok());
+
+            set_caller(accounts().alice);
+            faucet.disallow_account(accounts().charlie).unwrap();
+            set_now(1_000);
+            set_caller(accounts().charlie);
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::NotAllowlisted));
+        }
+
+        #[ink::test]
+        fn contract_balance_matches_funding() {
+            let mut faucet = faucet(1_000);
+            assert_eq!(faucet.contract_balance(), 1_000);
+            assert_eq!(faucet.get_stats_and_settings().remaining_claims, 10);
+
+            set_caller(accounts().bob);
+            test::transfer_in::<Env>(500);
+            faucet.fund().unwrap();
+            assert_eq!(faucet.contract_balance(), 1_500);
+        }
+
+        #[ink::test]
+        fn ip_timer_applies_after_account_timer_expires() {
+            let mut faucet = faucet(1_000);
+            faucet.set_ip_limit_timer(5_000).unwrap();
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+
+            // bob's own timer has run out but the IP address is still throttled
+            set_now(1_000);
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::IpRateLimited));
+            assert!(faucet.get_coin(b"10.0.0.2".to_vec()).is_ok());
+
+            set_now(5_000);
+            assert!(faucet.get_coin(IP.to_vec()).is_ok());
+        }
+
+        #[ink::test]
+        fn eligibility_results_are_enum_variants() {
+            let mut faucet = faucet(1_000);
+            set_caller(accounts().bob);
+            assert_eq!(faucet.check_eligibility(IP.to_vec()), Ok(true));
+            assert_eq!(faucet.eligibility_reason(IP.to_vec()), EligibilityStatus::Eligible);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            assert_eq!(faucet.check_eligibility(IP.to_vec()), Ok(false));
+            assert_eq!(
+                faucet.eligibility_reason(IP.to_vec()),
+                EligibilityStatus::RateLimited { seconds_remaining: 1_000 }
+            );
+        }
+
+        #[ink::test]
+        fn claim_state_is_written_before_a_second_claim() {
+            let mut faucet = faucet(1_000);
+            set_caller(accounts().bob);
+            let bob_before = balance_of(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::RateLimited));
+            assert_eq!(balance_of(accounts().bob), bob_before + 100);
+            assert_eq!(faucet.get_stats_and_settings().total_payouts, 100);
+        }
+
+        #[ink::test]
+        fn payouts_stop_at_the_reserve() {
+            let mut faucet = faucet(300);
+            faucet.set_reserve(150).unwrap();
+            set_caller(accounts().bob);
+            assert!(faucet.get_coin(IP.to_vec()).is_ok());
+            set_caller(accounts().charlie);
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::ContractDrained));
+            assert_eq!(balance_of(contract_id()), 200);
+        }
+
+        #[ink::test]
+        fn reset_user_can_claim_straight_away() {
+            let mut faucet = faucet(1_000);
+            set_now(50_000);
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            assert_eq!(faucet.reset_user_timer(accounts().bob), Err(Error::PermissionDenied));
+
+            set_caller(accounts().alice);
+            faucet.reset_user_timer(accounts().bob).unwrap();
+            // unknown users are a no-op
+            faucet.reset_user_timer(accounts().django).unwrap();
+            assert_eq!(faucet.get_user_details(accounts().django), None);
+
+            set_now(50_001);
+            set_caller(accounts().bob);
+            assert!(faucet.get_coin(IP.to_vec()).is_ok());
+        }
+
+        #[ink::test]
+        fn claim_event_topics_include_the_payout() {
+            let mut faucet = faucet(1_000);
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            let event = test::recorded_events().last().unwrap();
+            assert_eq!(event.topics.len(), 4);
+            assert_eq!(event.topics[0], <ClaimEvent as ink::env::Event>::SIGNATURE_TOPIC.unwrap().to_vec());
+            assert_eq!(event.topics[2], AsRef::<[u8]>::as_ref(&accounts().bob).to_vec());
+            let mut payout_topic = [0u8; 32];
+            payout_topic[..16].copy_from_slice(&100u128.to_le_bytes());
+            assert_eq!(event.topics[3], payout_topic.to_vec());
+        }
+
+        #[ink::test]
+        fn failed_claims_leave_no_failed_payout_count() {
+            let mut faucet = faucet(50);
+            assert_eq!(faucet.get_stats_and_settings().total_failed_payouts, 0);
+            set_caller(accounts().bob);
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::ContractDrained));
+            assert_eq!(faucet.get_stats_and_settings().total_failed_payouts, 0);
+        }
+
+        #[ink::test]
+        fn batch_counts_recipients_it_cannot_pay() {
+            let mut faucet = faucet(25);
+            let django_before = balance_of(accounts().django);
+            let recipients = vec![accounts().bob, accounts().charlie, accounts().django];
+            assert_eq!(faucet.batch_payout(recipients, 10), Ok(2));
+            assert_eq!(balance_of(accounts().django), django_before);
+            assert_eq!(faucet.get_stats_and_settings().total_failed_payouts, 1);
+        }
+
+        #[ink::test]
+        fn cleared_ip_accepts_new_accounts_again() {
+            let mut faucet = faucet(1_000);
+            faucet.set_limit_ip_total(1).unwrap();
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            set_caller(accounts().charlie);
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::IpLimitReached));
+            assert_eq!(faucet.clear_ip(IP.to_vec()), Err(Error::PermissionDenied));
+
+            set_caller(accounts().alice);
+            faucet.clear_ip(IP.to_vec()).unwrap();
+            assert_eq!(faucet.ip_account_count(IP.to_vec()), 0);
+            assert_eq!(faucet.get_stats_and_settings().total_unique_ips, 0);
+
+            set_caller(accounts().charlie);
+            assert!(faucet.get_coin(IP.to_vec()).is_ok());
+            assert_eq!(faucet.get_stats_and_settings().total_unique_ips, 1);
+        }
+
+        #[ink::test]
+        fn can_claim_reports_each_failure_without_changes() {
+            let mut faucet = faucet(1_000);
+            let bob = accounts().bob;
+            assert_eq!(faucet.can_claim(bob, IP.to_vec()), Ok(()));
+            assert_eq!(faucet.get_user_details(bob), None);
+
+            // timer
+            set_now(5_000);
+            set_caller(bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            assert_eq!(faucet.can_claim(bob, IP.to_vec()), Err(Error::RateLimited));
+            set_now(6_000);
+            assert_eq!(faucet.can_claim(bob, IP.to_vec()), Ok(()));
+
+            // IP address limit
+            set_caller(accounts().alice);
+            faucet.set_limit_ip_total(1).unwrap();
+            assert_eq!(faucet.can_claim(accounts().charlie, IP.to_vec()), Err(Error::IpLimitReached));
+            faucet.set_limit_ip_total(5).unwrap();
+
+            // blocklists
+            faucet.block_account(bob).unwrap();
+            assert_eq!(faucet.can_claim(bob, IP.to_vec()), Err(Error::AccountBlocked));
+            faucet.unblock_account(bob).unwrap();
+            faucet.block_ip(IP.to_vec()).unwrap();
+            assert_eq!(faucet.can_claim(bob, IP.to_vec()), Err(Error::IpBlocked));
+            faucet.unblock_ip(IP.to_vec()).unwrap();
+
+            // pause
+            faucet.set_paused(true).unwrap();
+            assert_eq!(faucet.can_claim(bob, IP.to_vec()), Err(Error::Paused));
+            faucet.set_paused(false).unwrap();
+
+            // balance
+            set_contract_balance(50);
+            assert_eq!(faucet.can_claim(bob, IP.to_vec()), Err(Error::ContractDrained));
+            assert_eq!(balance_of(contract_id()), 50);
+        }
+
+        #[ink::test]
+        fn token_decimals_are_set_and_read_back() {
+            let mut faucet = faucet(1_000);
+            assert_eq!(faucet.get_stats_and_settings().token_decimals, 0);
+            faucet.set_token_decimals(12).unwrap();
+            assert_eq!(faucet.get_stats_and_settings().token_decimals, 12);
+            set_caller(accounts().bob);
+            assert_eq!(faucet.set_token_decimals(18), Err(Error::PermissionDenied));
+            assert_eq!(faucet.get_stats_and_settings().token_decimals, 12);
+        }
+
+        #[ink::test]
+        fn max_accounts_stops_new_accounts_only() {
+            let mut faucet = faucet(1_000);
+            faucet.set_max_accounts(1).unwrap();
+            set_now(5_000);
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            set_caller(accounts().charlie);
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::MaxAccountsReached));
+
+            // the existing account can still reclaim
+            set_now(6_000);
+            set_caller(accounts().bob);
+            assert!(faucet.get_coin(IP.to_vec()).is_ok());
+
+            // zero is unlimited
+            set_caller(accounts().alice);
+            faucet.set_max_accounts(0).unwrap();
+            set_caller(accounts().charlie);
+            assert!(faucet.get_coin(IP.to_vec()).is_ok());
+        }
+
+        #[ink::test]
+        fn get_coin_returns_the_amount_paid() {
+            let mut faucet = faucet(1_000);
+            faucet.set_payouts_and_fund(100, 100, 300, 1000, 5, 0, 0).unwrap();
+            set_now(5_000);
+            set_caller(accounts().bob);
+            let bob_before = balance_of(accounts().bob);
+            assert_eq!(faucet.get_coin(IP.to_vec()).map(|receipt| receipt.amount), Ok(300));
+            assert_eq!(balance_of(accounts().bob), bob_before + 300);
+            set_now(6_000);
+            assert_eq!(faucet.get_coin(IP.to_vec()).map(|receipt| receipt.amount), Ok(100));
+        }
+
+        #[ink::test]
+        fn only_root_can_terminate() {
+            let mut faucet = faucet(1_000);
+            set_caller(accounts().bob);
+            assert_eq!(faucet.terminate(accounts().bob), Err(Error::PermissionDenied));
+            set_caller(accounts().alice);
+            assert_eq!(faucet.terminate(AccountId::from([0u8; 32])), Err(Error::InvalidRecipient));
+            assert_eq!(balance_of(contract_id()), 1_000);
+        }
+
+        #[ink::test]
+        fn terminate_waits_for_pending_claims() {
+            let mut faucet = faucet(1_000);
+            faucet.set_pending_timeout_ms(10_000).unwrap();
+            let (keypair, beneficiary) = signer(&mut faucet, 16);
+            let signature = sign_claim(&keypair, contract_id(), b"queue_claim", beneficiary, IP, 0);
+            faucet.queue_claim(beneficiary, IP.to_vec(), signature, 0).unwrap();
+            assert_eq!(faucet.terminate(accounts().alice), Err(Error::ClaimsPending));
+            assert_eq!(balance_of(contract_id()), 1_000);
+        }
+
+        #[ink::test]
+        fn block_cap_resets_in_the_next_block() {
+            let mut faucet = faucet(1_000);
+            faucet.set_max_claims_per_block(2).unwrap();
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            set_caller(accounts().charlie);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            set_caller(accounts().django);
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::BlockRateLimited));
+
+            test::advance_block::<Env>();
+            assert!(faucet.get_coin(IP.to_vec()).is_ok());
+        }
+
+        #[ink::test]
+        fn admins_operate_but_only_root_governs() {
+            let mut faucet = faucet(1_000);
+            let bob = accounts().bob;
+            set_caller(bob);
+            assert_eq!(faucet.set_paused(true), Err(Error::PermissionDenied));
+            assert_eq!(faucet.add_admin(bob), Err(Error::PermissionDenied));
+
+            set_caller(accounts().alice);
+            faucet.add_admin(bob).unwrap();
+            set_caller(bob);
+            faucet.set_paused(true).unwrap();
+            faucet.set_paused(false).unwrap();
+            faucet.block_account(accounts().charlie).unwrap();
+            faucet.unblock_account(accounts().charlie).unwrap();
+            faucet.set_payouts_and_fund(50, 50, 0, 1000, 5, 0, 0).unwrap();
+            assert_eq!(faucet.get_payout_amount(), 50);
+
+            // root only messages stay with root
+            assert_eq!(faucet.add_admin(accounts().charlie), Err(Error::PermissionDenied));
+            assert_eq!(faucet.remove_admin(bob), Err(Error::PermissionDenied));
+            assert_eq!(faucet.transfer_root(bob), Err(Error::PermissionDenied));
+
+            set_caller(accounts().alice);
+            faucet.remove_admin(bob).unwrap();
+            set_caller(bob);
+            assert_eq!(faucet.set_paused(true), Err(Error::PermissionDenied));
+        }
+
+        #[ink::test]
+        fn same_raw_ip_maps_to_one_hashed_key() {
+            let mut faucet = faucet(1_000);
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            set_caller(accounts().charlie);
+            faucet.get_coin(IP.to_vec()).unwrap();
+
+            let bob_ip = faucet.get_user_details(accounts().bob).unwrap().ip_address;
+            let charlie_ip = faucet.get_user_details(accounts().charlie).unwrap().ip_address;
+            assert_eq!(bob_ip, charlie_ip);
+            assert_eq!(bob_ip.len(), 32);
+            assert_ne!(bob_ip, IP.to_vec());
+            assert_eq!(faucet.ip_account_count(IP.to_vec()), 2);
+            assert_eq!(events_of::<ClaimEvent>()[0].user_ip, bob_ip);
+        }
+
+        #[ink::test]
+        fn dashboard_matches_stats_and_balance() {
+            let mut faucet = faucet(1_000);
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            let dashboard = faucet.get_dashboard();
+            assert_eq!(dashboard.stats, faucet.get_stats_and_settings());
+            assert_eq!(dashboard.contract_balance, 900);
+            assert_eq!(dashboard.contract_balance, balance_of(contract_id()));
+            assert!(!dashboard.stats.paused);
+            assert_eq!(dashboard.stats.remaining_claims, 9);
+            assert_eq!(dashboard.stats.total_payouts, 100);
+        }
+
+        #[ink::test]
+        fn old_payouts_roll_out_of_the_window() {
+            let mut faucet = faucet(1_000);
+            set_now(1_000);
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            set_now(2 * HOUR_MS + 1_000);
+            set_caller(accounts().charlie);
+            faucet.get_coin(IP.to_vec()).unwrap();
+
+            assert_eq!(faucet.payouts_in_window(HOUR_MS), 100);
+            assert_eq!(faucet.payouts_in_window(DAY_MS), 200);
+
+            // a day later the first claim is out of even the widest window
+            set_now(DAY_MS + HOUR_MS + 1_000);
+            assert_eq!(faucet.payouts_in_window(DAY_MS), 100);
+            set_now(3 * DAY_MS);
+            assert_eq!(faucet.payouts_in_window(DAY_MS), 0);
+        }
+
+        #[ink::test]
+        fn decayed_payout_shrinks_with_the_balance() {
+            let mut faucet = faucet(1_000);
+            test::set_value_transferred::<Env>(1_000);
+            faucet.set_payouts_and_fund(100, 100, 0, 1000, 5, 0, 0).unwrap();
+            test::set_value_transferred::<Env>(0);
+            faucet.set_decay_enabled(true).unwrap();
+
+            set_caller(accounts().bob);
+            assert_eq!(faucet.get_coin(IP.to_vec()).map(|receipt| receipt.amount), Ok(100));
+            set_caller(accounts().charlie);
+            assert_eq!(faucet.get_coin(IP.to_vec()).map(|receipt| receipt.amount), Ok(90));
+            set_caller(accounts().django);
+            assert_eq!(faucet.get_coin(IP.to_vec()).map(|receipt| receipt.amount), Ok(81));
+
+            // without decay the full payout is paid again
+            set_caller(accounts().alice);
+            faucet.set_decay_enabled(false).unwrap();
+            set_caller(accounts().eve);
+            assert_eq!(faucet.get_coin(IP.to_vec()).map(|receipt| receipt.amount), Ok(100));
+        }
+
+        #[ink::test]
+        fn root_queries_follow_the_root_account() {
+            let mut faucet = faucet(1_000);
+            assert!(faucet.is_root(accounts().alice));
+            assert!(!faucet.is_root(accounts().bob));
+            assert_eq!(faucet.get_root(), accounts().alice);
+
+            faucet.set_root_account(accounts().bob).unwrap();
+            assert!(!faucet.is_root(accounts().alice));
+            assert!(faucet.is_root(accounts().bob));
+            assert_eq!(faucet.get_root(), accounts().bob);
+        }
+
+        #[ink::test]
+        fn verify_accounts_mixes_known_and_unknown() {
+            let mut faucet = faucet(1_000);
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            assert_eq!(
+                faucet.verify_accounts(vec![accounts().bob, accounts().charlie, accounts().bob]),
+                vec![true, false, true]
+            );
+            // oversized lists are truncated
+            let many = vec![accounts().bob; MAX_VERIFY_ACCOUNTS + 5];
+            assert_eq!(faucet.verify_accounts(many).len(), MAX_VERIFY_ACCOUNTS);
+        }
+
+        #[ink::test]
+        fn disabled_events_still_update_counters() {
+            let mut faucet = faucet(1_000);
+            faucet.set_events_enabled(false).unwrap();
+            assert!(!faucet.get_stats_and_settings().events_enabled);
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            assert!(events_of::<ClaimEvent>().is_empty());
+            assert_eq!(faucet.get_stats_and_settings().total_payouts, 100);
+            assert_eq!(faucet.get_stats_and_settings().total_pebble_accounts, 1);
+
+            set_caller(accounts().alice);
+            faucet.set_events_enabled(true).unwrap();
+            set_caller(accounts().charlie);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            assert_eq!(events_of::<ClaimEvent>().len(), 1);
+        }
+
+        #[ink::test]
+        fn nonce_counts_successful_claims_only() {
+            let mut faucet = faucet(1_000);
+            set_now(5_000);
+            set_caller(accounts().bob);
+            assert_eq!(faucet.get_nonce(accounts().bob), 0);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            assert_eq!(faucet.get_nonce(accounts().bob), 1);
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::RateLimited));
+            assert_eq!(faucet.get_nonce(accounts().bob), 1);
+            set_now(6_000);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            assert_eq!(faucet.get_nonce(accounts().bob), 2);
+        }
+
+        #[ink::test]
+        fn funding_leaves_settings_alone() {
+            let mut faucet = faucet(1_000);
+            let before = faucet.get_stats_and_settings();
+            set_caller(accounts().bob);
+            test::set_value_transferred::<Env>(500);
+            faucet.fund().unwrap();
+
+            let funded = events_of::<FundedEvent>();
+            assert_eq!(funded.len(), 1);
+            assert_eq!(funded[0].funder, accounts().bob);
+            assert_eq!(funded[0].amount, 500);
+            let after = faucet.get_stats_and_settings();
+            assert_eq!(after.total_funded, before.total_funded + 500);
+            assert_eq!(after.get_payout, before.get_payout);
+            assert_eq!(after.eligible_payout, before.eligible_payout);
+            assert_eq!(after.limit_timer, before.limit_timer);
+            assert_eq!(after.limit_ip_total, before.limit_ip_total);
+        }
+
+        #[ink::test]
+        fn non_admin_funding_call_is_rejected() {
+            let mut faucet = faucet(1_000);
+            set_caller(accounts().bob);
+            let bob_before = balance_of(accounts().bob);
+            test::set_value_transferred::<Env>(500);
+            assert_eq!(
+                faucet.set_payouts_and_fund(1, 1, 0, 0, 1, 0, 0),
+                Err(Error::PermissionDenied)
+            );
+            assert!(events_of::<FundedEvent>().is_empty());
+            assert_eq!(faucet.get_stats_and_settings().total_funded, 0);
+            assert_eq!(faucet.get_payout_amount(), 100);
+            assert_eq!(balance_of(accounts().bob), bob_before);
+            assert_eq!(balance_of(contract_id()), 1_000);
+        }
+
+        #[ink::test]
+        fn claim_history_is_capped_oldest_first() {
+            let mut faucet = faucet(100_000);
+            set_caller(accounts().bob);
+            assert!(faucet.claim_history(accounts().bob).is_empty());
+            for claim in 1..=(MAX_CLAIM_HISTORY as u64 + 2) {
+                set_now(claim * 1_000);
+                faucet.get_coin(IP.to_vec()).unwrap();
+            }
+            let history = faucet.claim_history(accounts().bob);
+            assert_eq!(history.len(), MAX_CLAIM_HISTORY);
+            assert_eq!(history.first(), Some(&(3_000, 100)));
+            assert_eq!(history.last(), Some(&((MAX_CLAIM_HISTORY as u64 + 2) * 1_000, 100)));
+        }
+
+        #[ink::test]
+        fn rotating_ips_trips_the_account_limit() {
+            let mut faucet = faucet(1_000);
+            faucet.set_max_ips_per_account(2).unwrap();
+            set_caller(accounts().bob);
+            set_now(1_000);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            set_now(2_000);
+            faucet.get_coin(b"10.0.0.2".to_vec()).unwrap();
+            set_now(3_000);
+            assert_eq!(faucet.get_coin(b"10.0.0.3".to_vec()), Err(Error::TooManyIps));
+            // IP addresses already used are still fine
+            assert!(faucet.get_coin(IP.to_vec()).is_ok());
+        }
+
+        #[ink::test]
+        fn settings_change_emits_the_new_values() {
+            let mut faucet = faucet(1_000);
+            faucet.set_payouts_and_fund(70, 60, 200, 5_000, 3, 900, 400).unwrap();
+            let event = events_of::<SettingsChangedEvent>().pop().unwrap();
+            assert_eq!(event.eligible_payout, 70);
+            assert_eq!(event.get_payout, 60);
+            assert_eq!(event.first_claim_payout, 200);
+            assert_eq!(event.limit_timer, 5_000);
+            assert_eq!(event.limit_ip_total, 3);
+            assert_eq!(event.lifetime_cap, 900);
+            assert_eq!(event.daily_limit, 400);
+        }
+
+        #[ink::test]
+        fn preview_matches_first_and_repeat_claims() {
+            let mut faucet = faucet(1_000);
+            faucet.set_payouts_and_fund(100, 100, 300, 1000, 5, 0, 0).unwrap();
+            let bob = accounts().bob;
+            assert_eq!(faucet.preview_payout(bob), 300);
+            set_now(5_000);
+            set_caller(bob);
+            assert_eq!(faucet.get_coin(IP.to_vec()).map(|receipt| receipt.amount), Ok(300));
+            assert_eq!(faucet.preview_payout(bob), 100);
+            set_now(6_000);
+            assert_eq!(faucet.get_coin(IP.to_vec()).map(|receipt| receipt.amount), Ok(100));
+        }
+
+        #[ink::test]
+        fn pruning_keeps_recent_accounts() {
+            let mut faucet = faucet(1_000);
+            faucet.set_payouts_and_fund(100, 100, 300, 1000, 5, 0, 0).unwrap();
+            set_now(1_000);
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            set_now(10_000);
+            set_caller(accounts().charlie);
+            faucet.get_coin(IP.to_vec()).unwrap();
+
+            set_now(20_000);
+            let candidates = vec![accounts().bob, accounts().charlie, accounts().django];
+            assert_eq!(faucet.prune_stale(candidates.clone(), 15_000), Err(Error::PermissionDenied));
+            set_caller(accounts().alice);
+            assert_eq!(faucet.prune_stale(candidates, 15_000), Ok(1));
+            assert_eq!(faucet.get_stats_and_settings().total_pebble_accounts, 1);
+            assert_eq!(faucet.list_accounts(0, 10), vec![accounts().charlie]);
+            assert!(faucet.get_user_details(accounts().charlie).is_some());
+        }
+
+        #[ink::test]
+        fn pruned_account_is_not_a_new_user_again() {
+            let mut faucet = faucet(1_000);
+            faucet.set_payouts_and_fund(100, 100, 300, 1000, 5, 0, 0).unwrap();
+            set_now(1_000);
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            set_now(20_000);
+            set_caller(accounts().alice);
+            assert_eq!(faucet.prune_stale(vec![accounts().bob], 15_000), Ok(1));
+
+            // the pruned account keeps its last claim and lifetime payout
+            let tombstone = faucet.get_user_details(accounts().bob).unwrap();
+            assert_eq!(tombstone.timestamp, 1_000);
+            assert_eq!(tombstone.payout, 300);
+            assert!(faucet.verify_account(accounts().bob));
+            assert_eq!(faucet.get_nonce(accounts().bob), 1);
+
+            // the rest of its per account data is gone
+            assert!(faucet.claim_history(accounts().bob).is_empty());
+            assert_eq!(faucet.get_first_seen(accounts().bob), None);
+
+            set_caller(accounts().bob);
+            assert_eq!(faucet.get_coin(IP.to_vec()).map(|receipt| receipt.amount), Ok(100));
+            assert_eq!(faucet.get_user_details(accounts().bob).unwrap().payout, 400);
+            assert_eq!(faucet.get_nonce(accounts().bob), 2);
+            assert_eq!(faucet.list_accounts(0, 10), vec![accounts().bob]);
+        }
+
+        #[ink::test]
+        fn bypass_flag_only_affects_new_accounts() {
+            let mut faucet = faucet(1_000);
+            assert!(faucet.get_stats_and_settings().new_account_bypass_timer);
+            set_now(500);
+
+            // new accounts skip the timer by default
+            set_caller(accounts().bob);
+            assert!(faucet.get_coin(IP.to_vec()).is_ok());
+            // returning accounts always wait
+            set_now(1_000);
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::RateLimited));
+
+            // with the flag off new accounts wait too
+            set_caller(accounts().alice);
+            faucet.set_new_account_bypass_timer(false).unwrap();
+            set_now(900);
+            set_caller(accounts().charlie);
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::RateLimited));
+            set_now(1_600);
+            assert!(faucet.get_coin(IP.to_vec()).is_ok());
+            set_caller(accounts().bob);
+            assert!(faucet.get_coin(IP.to_vec()).is_ok());
+        }
+
+        #[ink::test]
+        fn relayed_claim_pays_the_signer() {
+            let mut faucet = faucet(1_000);
+            let (keypair, beneficiary) = signer(&mut faucet, 7);
+            let signature = sign_claim(&keypair, contract_id(), b"claim_for", beneficiary, IP, 0);
+
+            set_caller(accounts().bob);
+            assert_eq!(faucet.claim_for(beneficiary, IP.to_vec(), signature.clone(), 0), Ok(100));
+            assert_eq!(balance_of(beneficiary), 100);
+            assert_eq!(faucet.get_nonce(beneficiary), 1);
+            assert!(faucet.get_user_details(accounts().bob).is_none());
+
+            // the same signature cannot be replayed
+            set_now(5_000);
+            assert_eq!(faucet.claim_for(beneficiary, IP.to_vec(), signature, 0), Err(Error::BadNonce));
+        }
+
+        #[ink::test]
+        fn tampered_or_foreign_signatures_are_rejected() {
+            let mut faucet = faucet(1_000);
+            let (keypair, beneficiary) = signer(&mut faucet, 7);
+            let (_, other) = signer(&mut faucet, 8);
+            set_caller(accounts().bob);
+
+            // signed for another IP address
+            let signature = sign_claim(&keypair, contract_id(), b"claim_for", beneficiary, b"10.0.0.2", 0);
+            assert_eq!(faucet.claim_for(beneficiary, IP.to_vec(), signature, 0), Err(Error::BadSignature));
+
+            // a flipped bit
+            let mut signature = sign_claim(&keypair, contract_id(), b"claim_for", beneficiary, IP, 0);
+            signature[0] ^= 1;
+            assert_eq!(faucet.claim_for(beneficiary, IP.to_vec(), signature, 0), Err(Error::BadSignature));
+
+            // another account's claim
+            let signature = sign_claim(&keypair, contract_id(), b"claim_for", beneficiary, IP, 0);
+            assert_eq!(faucet.claim_for(other, IP.to_vec(), signature, 0), Err(Error::BadSignature));
+
+            // the wrong message tag or faucet
+            let signature = sign_claim(&keypair, contract_id(), b"get_coin_to", beneficiary, IP, 0);
+            assert_eq!(faucet.claim_for(beneficiary, IP.to_vec(), signature, 0), Err(Error::BadSignature));
+            let signature = sign_claim(&keypair, AccountId::from([0xFB; 32]), b"claim_for", beneficiary, IP, 0);
+            assert_eq!(faucet.claim_for(beneficiary, IP.to_vec(), signature, 0), Err(Error::BadSignature));
+
+            // short signatures
+            assert_eq!(faucet.claim_for(beneficiary, IP.to_vec(), vec![0; 10], 0), Err(Error::BadSignature));
+            assert_eq!(balance_of(beneficiary), 0);
+        }
+
+        #[ink::test]
+        fn ip_tag_count_saturates_towards_the_limit() {
+            assert_eq!(ContractStorage::tag_count(&[]), 0);
+            assert_eq!(ContractStorage::tag_count(&[accounts().bob, accounts().charlie]), 2);
+            let many = vec![accounts().bob; 300];
+            assert_eq!(ContractStorage::tag_count(&many), 300);
+
+            // a full IP address trips the limit for new accounts only
+            let mut faucet = faucet(1_000);
+            faucet.set_limit_ip_total(1).unwrap();
+            set_now(5_000);
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            assert_eq!(faucet.can_claim(accounts().charlie, IP.to_vec()), Err(Error::IpLimitReached));
+            set_now(6_000);
+            assert_eq!(faucet.can_claim(accounts().bob, IP.to_vec()), Ok(()));
+        }
+
+        #[ink::test]
+        fn restricted_lookups_need_an_allowlisted_caller() {
+            let mut faucet = faucet(1_000);
+            let bob = accounts().bob;
+            set_caller(bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+
+            // open by default
+            set_caller(accounts().charlie);
+            assert!(faucet.verify_account(bob));
+            assert!(faucet.get_user_details(bob).is_some());
+
+            set_caller(accounts().alice);
+            faucet.set_restrict_verify(true).unwrap();
+            set_caller(accounts().charlie);
+            assert!(!faucet.verify_account(bob));
+            assert_eq!(faucet.verify_accounts(vec![bob]), vec![false]);
+            assert_eq!(faucet.get_user_details(bob), None);
+            assert_eq!(faucet.get_nonce(bob), 0);
+            assert_eq!(faucet.allow_verifier(accounts().charlie), Err(Error::PermissionDenied));
+
+            // accounts can still look up their own data
+            set_caller(bob);
+            assert!(faucet.get_user_details(bob).is_some());
+            assert_eq!(faucet.get_nonce(bob), 1);
+
+            set_caller(accounts().alice);
+            faucet.allow_verifier(accounts().charlie).unwrap();
+            set_caller(accounts().charlie);
+            assert!(faucet.verify_account(bob));
+            assert_eq!(faucet.get_nonce(bob), 1);
+
+            set_caller(accounts().alice);
+            faucet.disallow_verifier(accounts().charlie).unwrap();
+            set_caller(accounts().charlie);
+            assert!(!faucet.verify_account(bob));
+        }
+
+        // this only covers the reward token settings, the PSP22 transfer itself is a
+        // cross contract call that the off-chain environment cannot run
+        #[ink::test]
+        fn reward_token_settings_are_root_only() {
+            let mut faucet = faucet(1_000);
+            let token = AccountId::from([0x70; 32]);
+            set_caller(accounts().bob);
+            assert_eq!(faucet.set_reward_token(Some(token), 5, true), Err(Error::PermissionDenied));
+
+            set_caller(accounts().alice);
+            faucet.set_reward_token(Some(token), 5, true).unwrap();
+            let stats = faucet.get_stats_and_settings();
+            assert_eq!(stats.reward_token, Some(token));
+            assert_eq!(stats.token_amount, 5);
+            assert!(stats.token_strict);
+            assert_eq!(stats.total_failed_token_transfers, 0);
+
+            faucet.set_reward_token(None, 0, false).unwrap();
+            assert_eq!(faucet.get_stats_and_settings().reward_token, None);
+        }
+
+        #[ink::test]
+        fn single_setters_leave_other_settings_alone() {
+            let mut faucet = faucet(1_000);
+            faucet.set_get_payout(40).unwrap();
+            assert_eq!(
+                (faucet.get_payout_amount(), faucet.eligible_payout(), faucet.limit_timer(), faucet.limit_ip_total()),
+                (40, 100, 1000, 5)
+            );
+            faucet.set_eligible_payout(30).unwrap();
+            assert_eq!(
+                (faucet.get_payout_amount(), faucet.eligible_payout(), faucet.limit_timer(), faucet.limit_ip_total()),
+                (40, 30, 1000, 5)
+            );
+            faucet.set_limit_timer(2_000).unwrap();
+            assert_eq!(
+                (faucet.get_payout_amount(), faucet.eligible_payout(), faucet.limit_timer(), faucet.limit_ip_total()),
+                (40, 30, 2_000, 5)
+            );
+            faucet.set_limit_ip_total(2).unwrap();
+            assert_eq!(
+                (faucet.get_payout_amount(), faucet.eligible_payout(), faucet.limit_timer(), faucet.limit_ip_total()),
+                (40, 30, 2_000, 2)
+            );
+
+            set_caller(accounts().bob);
+            assert_eq!(faucet.set_get_payout(1), Err(Error::PermissionDenied));
+            assert_eq!(faucet.set_eligible_payout(1), Err(Error::PermissionDenied));
+            assert_eq!(faucet.set_limit_timer(1), Err(Error::PermissionDenied));
+            assert_eq!(faucet.set_limit_ip_total(1), Err(Error::PermissionDenied));
+        }
+
+        #[ink::test]
+        fn memo_is_emitted_and_length_checked() {
+            let mut faucet = faucet(1_000);
+            set_caller(accounts().bob);
+            assert_eq!(
+                faucet.get_coin_with_memo(IP.to_vec(), vec![b'x'; MAX_MEMO_LEN + 1]),
+                Err(Error::MemoTooLong)
+            );
+            assert_eq!(faucet.get_user_details(accounts().bob), None);
+
+            assert_eq!(faucet.get_coin_with_memo(IP.to_vec(), b"tweet-42".to_vec()), Ok(100));
+            let event = events_of::<ClaimMemoEvent>().pop().unwrap();
+            assert_eq!(event.memo, b"tweet-42".to_vec());
+            assert_eq!(event.pebble, accounts().bob);
+            assert_eq!(event.payout, 100);
+        }
+
+        #[ink::test]
+        fn sponsored_claim_uses_the_recipients_limits() {
+            let mut faucet = faucet(1_000);
+            let recipient = accounts().eve;
+            let recipient_before = balance_of(recipient);
+            set_now(5_000);
+
+            // the caller's own timer does not matter
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            assert_eq!(faucet.get_coin_to(recipient, IP.to_vec()), Ok(100));
+            assert_eq!(balance_of(recipient), recipient_before + 100);
+            assert_eq!(faucet.get_user_details(recipient).unwrap().timestamp, 5_000);
+            assert_eq!(faucet.get_user_details(accounts().bob).unwrap().payout, 100);
+
+            // the recipient's timer does
+            set_caller(accounts().charlie);
+            assert_eq!(faucet.get_coin_to(recipient, IP.to_vec()), Err(Error::RateLimited));
+            assert_eq!(faucet.get_user_details(accounts().charlie), None);
+            set_now(6_000);
+            assert_eq!(faucet.get_coin_to(recipient, IP.to_vec()), Ok(100));
+        }
+
+        #[ink::test]
+        fn sponsored_claim_checks_the_recipient() {
+            let mut faucet = faucet(1_000);
+            let recipient = accounts().eve;
+            let recipient_before = balance_of(recipient);
+            set_caller(accounts().bob);
+            assert_eq!(
+                faucet.get_coin_to(AccountId::from([0u8; 32]), IP.to_vec()),
+                Err(Error::InvalidRecipient)
+            );
+
+            // the recipient's IP address limit applies
+            set_caller(accounts().alice);
+            faucet.set_limit_ip_total(1).unwrap();
+            set_caller(accounts().charlie);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            set_caller(accounts().bob);
+            assert_eq!(faucet.get_coin_to(recipient, IP.to_vec()), Err(Error::IpLimitReached));
+
+            // a blocked recipient cannot be sponsored, and a blocked caller cannot sponsor anyone
+            set_caller(accounts().alice);
+            faucet.set_limit_ip_total(5).unwrap();
+            faucet.block_account(recipient).unwrap();
+            set_caller(accounts().bob);
+            assert_eq!(faucet.get_coin_to(recipient, IP.to_vec()), Err(Error::AccountBlocked));
+            set_caller(accounts().alice);
+            faucet.unblock_account(recipient).unwrap();
+            faucet.block_account(accounts().bob).unwrap();
+            set_caller(accounts().bob);
+            assert_eq!(faucet.get_coin_to(recipient, IP.to_vec()), Err(Error::AccountBlocked));
+            assert_eq!(balance_of(recipient), recipient_before);
+        }
+
+        #[ink::test]
+        fn migrate_bumps_the_storage_version() {
+            let mut faucet = faucet(1_000);
+            let version = faucet.get_stats_and_settings().storage_version;
+            faucet.migrate().unwrap();
+            assert_eq!(faucet.get_stats_and_settings().storage_version, version + 1);
+            set_caller(accounts().bob);
+            assert_eq!(faucet.migrate(), Err(Error::PermissionDenied));
+            assert_eq!(faucet.get_stats_and_settings().storage_version, version + 1);
+        }
+
+        #[ink::test]
+        fn new_accounts_wait_out_the_minimum_age() {
+            let mut faucet = faucet(1_000);
+            faucet.set_min_account_age_ms(10_000).unwrap();
+            set_now(1_000);
+            set_caller(accounts().bob);
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::AccountTooNew));
+            assert_eq!(faucet.check_in(), 1_000);
+            set_now(5_000);
+            assert_eq!(faucet.check_in(), 1_000);
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::AccountTooNew));
+            set_now(11_000);
+            assert!(faucet.get_coin(IP.to_vec()).is_ok());
+
+            // an attested first seen time counts too
+            set_caller(accounts().alice);
+            faucet.attest_first_seen(accounts().charlie, 0).unwrap();
+            assert_eq!(faucet.get_first_seen(accounts().charlie), Some(0));
+            set_caller(accounts().charlie);
+            assert!(faucet.get_coin(IP.to_vec()).is_ok());
+        }
+
+        #[ink::test]
+        fn referral_bonus_scales_up_to_the_cap() {
+            let mut faucet = faucet(10_000);
+            faucet.set_referral_multiplier_bps(5_000).unwrap();
+            let bob = accounts().bob;
+            assert_eq!(faucet.preview_payout(bob), 100);
+
+            set_caller(accounts().charlie);
+            faucet.get_coin_with_referrer(IP.to_vec(), bob).unwrap();
+            assert_eq!(faucet.referral_count(bob), 1);
+            assert_eq!(faucet.preview_payout(bob), 150);
+            set_caller(accounts().django);
+            faucet.get_coin_with_referrer(IP.to_vec(), bob).unwrap();
+            assert_eq!(faucet.preview_payout(bob), 200);
+            set_caller(bob);
+            assert_eq!(faucet.get_coin(IP.to_vec()).map(|receipt| receipt.amount), Ok(200));
+
+            // the multiplier never goes past MAX_REFERRAL_MULTIPLIER_BPS
+            set_caller(accounts().alice);
+            faucet.set_referral_multiplier_bps(u16::MAX).unwrap();
+            assert_eq!(faucet.preview_payout(bob), 300);
+        }
+
+        #[ink::test]
+        fn zero_payout_record_still_waits_out_the_timer() {
+            let mut faucet = faucet(1_000);
+            let bob = accounts().bob;
+            set_now(5_000);
+            set_caller(bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+
+            // an existing account whose recorded payout was reset to zero
+            let mut details = faucet.user_map.get(bob).unwrap();
+            details.payout = 0;
+            faucet.user_map.insert(bob, &details);
+
+            set_now(5_500);
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::RateLimited));
+            set_now(6_000);
+            assert!(faucet.get_coin(IP.to_vec()).is_ok());
+        }
+
+        #[ink::test]
+        fn payouts_over_the_cap_are_rejected() {
+            let mut faucet = faucet(1_000);
+            faucet.set_max_payout_cap(200).unwrap();
+            assert_eq!(faucet.set_payouts_and_fund(100, 201, 0, 1000, 5, 0, 0), Err(Error::PayoutTooHigh));
+            assert_eq!(faucet.set_payouts_and_fund(201, 100, 0, 1000, 5, 0, 0), Err(Error::PayoutTooHigh));
+            assert_eq!(faucet.set_get_payout(201), Err(Error::PayoutTooHigh));
+            assert_eq!(faucet.set_eligible_payout(201), Err(Error::PayoutTooHigh));
+            assert_eq!(faucet.get_payout_amount(), 100);
+
+            faucet.set_payouts_and_fund(200, 200, 0, 1000, 5, 0, 0).unwrap();
+            assert_eq!(faucet.get_payout_amount(), 200);
+            faucet.set_get_payout(150).unwrap();
+            assert_eq!(faucet.get_payout_amount(), 150);
+
+            set_caller(accounts().bob);
+            assert_eq!(faucet.set_max_payout_cap(0), Err(Error::PermissionDenied));
+        }
+
+        #[ink::test]
+        fn daily_remaining_tracks_todays_spend() {
+            let mut faucet = faucet(1_000);
+            assert_eq!(faucet.daily_remaining(), Balance::MAX);
+            faucet.set_payouts_and_fund(100, 100, 0, 1000, 5, 0, 250).unwrap();
+            assert_eq!(faucet.daily_remaining(), 250);
+            set_now(1_000);
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            assert_eq!(faucet.daily_remaining(), 150);
+
+            // a new day is computed without being saved
+            set_now(DAY_MS + 1_000);
+            assert_eq!(faucet.daily_remaining(), 250);
+            assert_eq!(faucet.get_stats_and_settings().day_spent, 100);
+        }
+
+        #[ink::test]
+        fn metadata_is_set_and_length_checked() {
+            let mut faucet = faucet(1_000);
+            assert_eq!(faucet.get_metadata(), (Vec::new(), Vec::new()));
+            faucet.set_metadata(b"Geode Faucet".to_vec(), b"test coin".to_vec()).unwrap();
+            assert_eq!(faucet.get_metadata(), (b"Geode Faucet".to_vec(), b"test coin".to_vec()));
+
+            assert_eq!(
+                faucet.set_metadata(vec![b'n'; MAX_NAME_LEN + 1], Vec::new()),
+                Err(Error::MetadataTooLong)
+            );
+            assert_eq!(
+                faucet.set_metadata(Vec::new(), vec![b'd'; MAX_DESCRIPTION_LEN + 1]),
+                Err(Error::MetadataTooLong)
+            );
+            set_caller(accounts().bob);
+            assert_eq!(faucet.set_metadata(Vec::new(), Vec::new()), Err(Error::PermissionDenied));
+            assert_eq!(faucet.get_metadata(), (b"Geode Faucet".to_vec(), b"test coin".to_vec()));
+        }
+
+        #[ink::test]
+        fn occupied_ip_takes_no_second_account() {
+            let mut faucet = faucet(1_000);
+            faucet.set_one_account_per_ip(true).unwrap();
+            set_now(5_000);
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            set_caller(accounts().charlie);
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::IpAlreadyUsed));
+            assert!(faucet.get_coin(b"10.0.0.2".to_vec()).is_ok());
+
+            // the account already on the IP address can reclaim
+            set_now(6_000);
+            set_caller(accounts().bob);
+            assert!(faucet.get_coin(IP.to_vec()).is_ok());
+        }
+
+        #[ink::test]
+        fn deploying_with_value_emits_a_funding_event() {
+            test::set_callee::<Env>(contract_id());
+            set_contract_balance(1_000);
+            set_caller(accounts().bob);
+            test::set_value_transferred::<Env>(1_000);
+            let faucet = ContractStorage::new(accounts().alice).unwrap();
+
+            let funded = events_of::<FundedEvent>();
+            assert_eq!(funded.len(), 1);
+            assert_eq!(funded[0].funder, accounts().bob);
+            assert_eq!(funded[0].amount, 1_000);
+            assert_eq!(funded[0].new_balance, 1_000);
+            assert_eq!(faucet.total_funded(), 1_000);
+        }
+
+        #[ink::test]
+        fn funding_through_settings_emits_a_funding_event() {
+            let mut faucet = faucet(1_000);
+            assert!(events_of::<FundedEvent>().is_empty());
+            test::set_value_transferred::<Env>(300);
+            faucet.set_payouts_and_fund(100, 100, 0, 1000, 5, 0, 0).unwrap();
+            let funded = events_of::<FundedEvent>();
+            assert_eq!(funded.len(), 1);
+            assert_eq!(funded[0].funder, accounts().alice);
+            assert_eq!(funded[0].amount, 300);
+            assert_eq!(faucet.total_funded(), 300);
+        }
+
+        #[ink::test]
+        fn right_code_skips_timers_and_ip_limits_only() {
+            let mut faucet = faucet(1_000);
+            let mut code_hash = <Blake2x256 as HashOutput>::Type::default();
+            ink::env::hash_bytes::<Blake2x256>(b"open sesame", &mut code_hash);
+            faucet.set_bypass_code_hash(Some(Hash::from(code_hash))).unwrap();
+            faucet.set_limit_ip_total(1).unwrap();
+
+            set_now(5_000);
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            // a wrong code is a normal claim
+            assert_eq!(faucet.get_coin_with_code(IP.to_vec(), b"wrong".to_vec()), Err(Error::RateLimited));
+            assert_eq!(faucet.get_coin_with_code(IP.to_vec(), b"open sesame".to_vec()), Ok(100));
+            // the code only works once per account
+            assert_eq!(faucet.get_coin_with_code(IP.to_vec(), b"open sesame".to_vec()), Err(Error::RateLimited));
+
+            set_caller(accounts().charlie);
+            assert_eq!(faucet.get_coin_with_code(IP.to_vec(), b"wrong".to_vec()), Err(Error::IpLimitReached));
+            assert_eq!(faucet.get_coin_with_code(IP.to_vec(), b"open sesame".to_vec()), Ok(100));
+
+            // blocklists still apply
+            set_caller(accounts().alice);
+            faucet.block_account(accounts().django).unwrap();
+            set_caller(accounts().django);
+            assert_eq!(
+                faucet.get_coin_with_code(IP.to_vec(), b"open sesame".to_vec()),
+                Err(Error::AccountBlocked)
+            );
+
+            // a new code can be used again
+            let mut new_code_hash = <Blake2x256 as HashOutput>::Type::default();
+            ink::env::hash_bytes::<Blake2x256>(b"next event", &mut new_code_hash);
+            set_caller(accounts().alice);
+            faucet.set_bypass_code_hash(Some(Hash::from(new_code_hash))).unwrap();
+            set_caller(accounts().bob);
+            assert_eq!(faucet.get_coin_with_code(IP.to_vec(), b"next event".to_vec()), Ok(100));
+        }
+
+        #[ink::test]
+        fn reset_zeroes_counters_but_keeps_user_data() {
+            let mut faucet = faucet(1_000);
+            faucet.set_max_accounts(2).unwrap();
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            set_caller(accounts().charlie);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            assert_eq!(faucet.reset_stats(), Err(Error::PermissionDenied));
+
+            set_caller(accounts().alice);
+            faucet.reset_stats().unwrap();
+            let event = events_of::<StatsResetEvent>().pop().unwrap();
+            assert_eq!(event.total_payouts, 200);
+            assert_eq!(event.total_pebble_accounts, 2);
+            let stats = faucet.get_stats_and_settings();
+            assert_eq!(stats.total_payouts, 0);
+            assert_eq!(faucet.payouts_in_window(DAY_MS), 0);
+
+            // the live gauges still match the stored accounts and IP addresses
+            assert_eq!(stats.total_pebble_accounts, 2);
+            assert_eq!(stats.total_unique_ips, 1);
+            assert_eq!(faucet.account_count(), 2);
+
+            // per user and per IP address data stays
+            assert_eq!(faucet.get_user_details(accounts().bob).unwrap().payout, 100);
+            assert_eq!(faucet.ip_account_count(IP.to_vec()), 2);
+            assert!(faucet.verify_account(accounts().charlie));
+
+            // and max_accounts still counts the accounts from before the reset
+            set_caller(accounts().django);
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::MaxAccountsReached));
+        }
+
+        #[ink::test]
+        fn payouts_below_the_minimum_are_rejected_or_bumped() {
+            let mut faucet = faucet(1_000);
+            faucet.set_min_transfer(101, false).unwrap();
+            set_caller(accounts().bob);
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::PayoutBelowMinimum));
+
+            // a payout right at the minimum is fine
+            set_caller(accounts().alice);
+            faucet.set_min_transfer(100, false).unwrap();
+            set_caller(accounts().bob);
+            assert_eq!(faucet.get_coin(IP.to_vec()).map(|receipt| receipt.amount), Ok(100));
+
+            set_caller(accounts().alice);
+            faucet.set_min_transfer(150, true).unwrap();
+            assert_eq!(faucet.preview_payout(accounts().charlie), 150);
+            set_caller(accounts().charlie);
+            assert_eq!(faucet.get_coin(IP.to_vec()).map(|receipt| receipt.amount), Ok(150));
+        }
+
+        #[ink::test]
+        fn min_transfer_respects_the_payout_cap() {
+            let mut faucet = faucet(1_000);
+            faucet.set_max_payout_cap(120).unwrap();
+            assert_eq!(faucet.set_min_transfer(121, true), Err(Error::PayoutTooHigh));
+            faucet.set_min_transfer(120, true).unwrap();
+            assert_eq!(faucet.get_stats_and_settings().min_transfer, 120);
+            set_caller(accounts().bob);
+            assert_eq!(faucet.set_min_transfer(1, false), Err(Error::PermissionDenied));
+        }
+
+        #[ink::test]
+        fn single_getters_match_storage() {
+            let mut faucet = faucet(1_000);
+            faucet.set_payouts_and_fund(70, 60, 0, 4_000, 3, 0, 0).unwrap();
+            assert_eq!(faucet.eligible_payout(), faucet.eligible_payout);
+            assert_eq!(faucet.get_payout_amount(), faucet.get_payout);
+            assert_eq!(faucet.limit_timer(), faucet.limit_timer);
+            assert_eq!(faucet.limit_ip_total(), faucet.limit_ip_total);
+            assert_eq!(
+                (faucet.eligible_payout(), faucet.get_payout_amount(), faucet.limit_timer(), faucet.limit_ip_total()),
+                (70, 60, 4_000, 3)
+            );
+        }
+
+        #[ink::test]
+        fn zero_address_roots_and_recipients_are_rejected() {
+            let zero = AccountId::from([0u8; 32]);
+            test::set_callee::<Env>(contract_id());
+            assert!(matches!(ContractStorage::new(zero), Err(Error::InvalidRoot)));
+
+            let mut faucet = faucet(1_000);
+            assert_eq!(faucet.set_root_account(zero), Err(Error::InvalidRoot));
+            assert_eq!(faucet.transfer_root(zero), Err(Error::InvalidRoot));
+            assert_eq!(faucet.get_root(), accounts().alice);
+
+            assert_eq!(faucet.withdraw_funds(100, zero), Err(Error::InvalidRecipient));
+            assert_eq!(faucet.sweep_all(zero), Err(Error::InvalidRecipient));
+            assert_eq!(faucet.batch_payout(vec![accounts().bob, zero], 10), Err(Error::InvalidRecipient));
+            set_caller(accounts().bob);
+            assert_eq!(faucet.get_coin_with_referrer(IP.to_vec(), zero), Err(Error::InvalidRecipient));
+            assert_eq!(balance_of(contract_id()), 1_000);
+        }
+
+        #[ink::test]
+        fn streak_builds_and_breaks_over_time() {
+            let mut faucet = faucet(10_000);
+            faucet.set_streak_bonus(10).unwrap();
+            let bob = accounts().bob;
+            set_caller(bob);
+            set_now(1_000);
+            assert_eq!(faucet.get_coin(IP.to_vec()).map(|receipt| receipt.amount), Ok(100));
+            assert_eq!(faucet.get_streak(bob), 1);
+
+            // within twice the limit_timer the streak grows and the bonus is paid
+            set_now(2_000);
+            assert_eq!(faucet.preview_payout(bob), 110);
+            assert_eq!(faucet.get_coin(IP.to_vec()).map(|receipt| receipt.amount), Ok(110));
+            set_now(4_000);
+            assert_eq!(faucet.get_coin(IP.to_vec()).map(|receipt| receipt.amount), Ok(110));
+            assert_eq!(faucet.get_streak(bob), 3);
+
+            // a longer gap starts it again without the bonus
+            set_now(6_001);
+            assert_eq!(faucet.preview_payout(bob), 100);
+            assert_eq!(faucet.get_coin(IP.to_vec()).map(|receipt| receipt.amount), Ok(100));
+            assert_eq!(faucet.get_streak(bob), 1);
+        }
+
+        #[ink::test]
+        fn claims_only_inside_the_window() {
+            let mut faucet = faucet(1_000);
+            faucet.set_claim_window(10_000, 20_000).unwrap();
+            let stats = faucet.get_stats_and_settings();
+            assert_eq!((stats.open_time, stats.close_time), (10_000, 20_000));
+            set_caller(accounts().bob);
+            set_now(9_999);
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::FaucetClosed));
+            set_now(10_000);
+            assert!(faucet.get_coin(IP.to_vec()).is_ok());
+            set_now(20_001);
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::FaucetClosed));
+
+            // zero leaves that end open
+            set_caller(accounts().alice);
+            faucet.set_claim_window(10_000, 0).unwrap();
+            set_caller(accounts().bob);
+            assert!(faucet.get_coin(IP.to_vec()).is_ok());
+        }
+
+        #[ink::test]
+        fn total_funded_adds_up_every_funding() {
+            let mut faucet = faucet(1_000);
+            assert_eq!(faucet.total_funded(), 0);
+            test::set_value_transferred::<Env>(200);
+            faucet.set_payouts_and_fund(100, 100, 0, 1000, 5, 0, 0).unwrap();
+            set_caller(accounts().bob);
+            test::set_value_transferred::<Env>(50);
+            faucet.fund().unwrap();
+            faucet.fund().unwrap();
+            assert_eq!(faucet.total_funded(), 300);
+            assert_eq!(faucet.get_stats_and_settings().total_funded, 300);
+        }
+
+        #[ink::test]
+        fn ip_payout_total_sums_claims_from_one_ip() {
+            let mut faucet = faucet(1_000);
+            set_now(5_000);
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            set_now(6_000);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            set_caller(accounts().charlie);
+            faucet.get_coin(b"10.0.0.2".to_vec()).unwrap();
+            assert_eq!(faucet.ip_payout_total(IP.to_vec()), 200);
+            assert_eq!(faucet.ip_payout_total(b"10.0.0.2".to_vec()), 100);
+            assert_eq!(faucet.ip_payout_total(b"10.0.0.3".to_vec()), 0);
+        }
+
+        #[ink::test]
+        fn no_claims_inside_a_maintenance_window() {
+            let mut faucet = faucet(1_000);
+            faucet.add_maintenance_window(10_000, 20_000).unwrap();
+            assert_eq!(faucet.get_maintenance_windows(), vec![(10_000, 20_000)]);
+            set_caller(accounts().bob);
+            assert_eq!(faucet.add_maintenance_window(0, 1), Err(Error::PermissionDenied));
+            set_now(15_000);
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::FaucetClosed));
+            set_now(20_001);
+            assert!(faucet.get_coin(IP.to_vec()).is_ok());
+
+            set_caller(accounts().alice);
+            for window in 1..MAX_MAINTENANCE_WINDOWS as u64 {
+                faucet.add_maintenance_window(window, window).unwrap();
+            }
+            assert_eq!(faucet.add_maintenance_window(0, 0), Err(Error::TooManyWindows));
+            faucet.clear_maintenance_windows().unwrap();
+            assert!(faucet.get_maintenance_windows().is_empty());
+            set_now(15_000);
+            set_caller(accounts().charlie);
+            assert!(faucet.get_coin(IP.to_vec()).is_ok());
+        }
+
+        #[ink::test]
+        fn pending_claim_is_finalized_before_expiry() {
+            let mut faucet = faucet(1_000);
+            faucet.set_pending_timeout_ms(10_000).unwrap();
+            let (keypair, beneficiary) = signer(&mut faucet, 11);
+            set_now(5_000);
+            set_caller(accounts().bob);
+            let signature = sign_claim(&keypair, contract_id(), b"queue_claim", beneficiary, IP, 0);
+            assert_eq!(faucet.queue_claim(beneficiary, IP.to_vec(), signature, 0), Ok(100));
+            assert_eq!(faucet.get_pending_claim(beneficiary), Some((100, 15_000)));
+            assert_eq!(balance_of(beneficiary), 0);
+            assert!(events_of::<ClaimEvent>().is_empty());
+
+            // only one claim can be pending at once
+            let signature = sign_claim(&keypair, contract_id(), b"queue_claim", beneficiary, IP, 1);
+            assert_eq!(faucet.queue_claim(beneficiary, IP.to_vec(), signature, 1), Err(Error::ClaimPending));
+            assert_eq!(faucet.expire_claim(beneficiary), Err(Error::ClaimNotExpired));
+
+            set_now(15_000);
+            assert_eq!(faucet.finalize_claim(beneficiary), Ok(100));
+            assert_eq!(balance_of(beneficiary), 100);
+            assert_eq!(faucet.get_pending_claim(beneficiary), None);
+            assert_eq!(faucet.get_stats_and_settings().pending_total, 0);
+            assert_eq!(events_of::<ClaimEvent>().len(), 1);
+            assert_eq!(faucet.finalize_claim(beneficiary), Err(Error::NoPendingClaim));
+        }
+
+        #[ink::test]
+        fn expired_claim_rolls_back_its_counters() {
+            let mut faucet = faucet(1_000);
+            faucet.set_payouts_and_fund(100, 100, 0, 1000, 5, 0, 500).unwrap();
+            faucet.set_pending_timeout_ms(10_000).unwrap();
+            let (keypair, beneficiary) = signer(&mut faucet, 11);
+            set_now(5_000);
+            set_caller(accounts().bob);
+            let signature = sign_claim(&keypair, contract_id(), b"queue_claim", beneficiary, IP, 0);
+            faucet.queue_claim(beneficiary, IP.to_vec(), signature, 0).unwrap();
+
+            set_now(15_001);
+            assert_eq!(faucet.finalize_claim(beneficiary), Err(Error::ClaimExpired));
+            assert_eq!(faucet.expire_claim(beneficiary), Ok(100));
+            let stats = faucet.get_stats_and_settings();
+            assert_eq!(stats.total_payouts, 0);
+            assert_eq!(stats.pending_total, 0);
+            assert_eq!(stats.day_spent, 0);
+            assert_eq!(faucet.payouts_in_window(DAY_MS), 0);
+            assert_eq!(faucet.ip_payout_total(IP.to_vec()), 0);
+            assert_eq!(faucet.get_user_details(beneficiary).unwrap().payout, 0);
+            assert!(faucet.claim_history(beneficiary).is_empty());
+            assert_eq!(balance_of(contract_id()), 1_000);
+            assert_eq!(balance_of(beneficiary), 0);
+        }
+
+        #[ink::test]
+        fn withdrawals_leave_pending_claims_covered() {
+            let mut faucet = faucet(1_000);
+            faucet.set_pending_timeout_ms(10_000).unwrap();
+            let (keypair, beneficiary) = signer(&mut faucet, 11);
+            set_caller(accounts().bob);
+            let signature = sign_claim(&keypair, contract_id(), b"queue_claim", beneficiary, IP, 0);
+            faucet.queue_claim(beneficiary, IP.to_vec(), signature, 0).unwrap();
+
+            set_caller(accounts().alice);
+            assert_eq!(faucet.withdraw_funds(901, accounts().alice), Err(Error::InsufficientBalance));
+            faucet.withdraw_funds(900, accounts().alice).unwrap();
+            assert_eq!(faucet.sweep_all(accounts().alice), Ok(0));
+            assert_eq!(faucet.finalize_claim(beneficiary), Ok(100));
+            assert_eq!(balance_of(contract_id()), 0);
+        }
+
+        #[ink::test]
+        fn my_status_describes_a_known_user() {
+            let mut faucet = faucet(1_000);
+            set_now(5_000);
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            set_now(5_400);
+            assert_eq!(faucet.my_status(IP.to_vec()), UserStatus {
+                verified: true,
+                blocked: false,
+                eligibility: EligibilityStatus::RateLimited { seconds_remaining: 600 },
+                seconds_remaining: 600,
+                last_claim: 5_000,
+                preview_payout: 100,
+            });
+
+            set_caller(accounts().alice);
+            faucet.block_ip(IP.to_vec()).unwrap();
+            set_caller(accounts().bob);
+            let status = faucet.my_status(IP.to_vec());
+            assert!(status.blocked);
+            assert_eq!(status.eligibility, EligibilityStatus::Blocked);
+
+            set_caller(accounts().charlie);
+            let status = faucet.my_status(b"10.0.0.2".to_vec());
+            assert!(!status.verified);
+            assert_eq!(status.eligibility, EligibilityStatus::Eligible);
+            assert_eq!(status.seconds_remaining, 0);
+        }
+
+        #[ink::test]
+        fn saturated_ip_takes_no_new_accounts() {
+            let mut faucet = faucet(1_000);
+            faucet.set_limit_ip_total(u128::MAX).unwrap();
+            let tagged: Vec<AccountId> = (0..MAX_IP_VEC_LEN)
+                .map(|index| AccountId::from([index as u8 + 0x80; 32]))
+                .collect();
+            faucet.ipaddress_count.insert(ContractStorage::hash_ip(IP), &tagged);
+
+            set_caller(accounts().bob);
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::IpLimitReached));
+            assert_eq!(faucet.ip_account_count(IP.to_vec()), MAX_IP_VEC_LEN as u128);
+
+            // accounts already on the list can still claim
+            set_caller(tagged[0]);
+            assert!(faucet.get_coin(IP.to_vec()).is_ok());
+            assert_eq!(faucet.ip_account_count(IP.to_vec()), MAX_IP_VEC_LEN as u128);
+        }
+
+        #[ink::test]
+        fn eligibility_is_checked_per_ip() {
+            let mut faucet = faucet(1_000);
+            faucet.set_limit_ip_total(1).unwrap();
+            faucet.block_ip(b"10.0.0.3".to_vec()).unwrap();
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+
+            let ips = vec![IP.to_vec(), b"10.0.0.2".to_vec(), b"10.0.0.3".to_vec()];
+            assert_eq!(faucet.eligibility_for_ips(accounts().charlie, ips), vec![false, true, false]);
+
+            // oversized lists are truncated
+            let many = vec![b"10.0.0.2".to_vec(); MAX_ELIGIBILITY_IPS + 2];
+            assert_eq!(faucet.eligibility_for_ips(accounts().charlie, many).len(), MAX_ELIGIBILITY_IPS);
+        }
+
+        #[ink::test]
+        fn payouts_round_to_the_unit() {
+            let mut faucet = faucet(1_000);
+            faucet.set_round_to(30).unwrap();
+            assert_eq!(faucet.get_stats_and_settings().round_to, 30);
+            let bob = accounts().bob;
+            // above the unit rounds down
+            assert_eq!(faucet.preview_payout(bob), 90);
+            set_caller(bob);
+            assert_eq!(faucet.get_coin(IP.to_vec()).map(|receipt| receipt.amount), Ok(90));
+
+            // below the unit falls back to the unit
+            set_caller(accounts().alice);
+            faucet.set_round_to(150).unwrap();
+            assert_eq!(faucet.preview_payout(accounts().charlie), 150);
+
+            // a zero payout stays zero
+            faucet.set_get_payout(0).unwrap();
+            assert_eq!(faucet.preview_payout(accounts().charlie), 0);
+        }
+
+        #[ink::test]
+        fn block_and_unblock_actions_emit_events() {
+            let mut faucet = faucet(1_000);
+            set_now(7_000);
+            faucet.block_account(accounts().bob).unwrap();
+            faucet.unblock_account(accounts().bob).unwrap();
+            faucet.block_ip(IP.to_vec()).unwrap();
+            faucet.unblock_ip(IP.to_vec()).unwrap();
+
+            let blocked = events_of::<AccountBlockedEvent>();
+            assert_eq!(blocked.len(), 1);
+            assert_eq!(
+                (blocked[0].account, blocked[0].admin, blocked[0].timestamp),
+                (accounts().bob, accounts().alice, 7_000)
+            );
+            let unblocked = events_of::<AccountUnblockedEvent>();
+            assert_eq!(unblocked.len(), 1);
+            assert_eq!((unblocked[0].account, unblocked[0].admin), (accounts().bob, accounts().alice));
+
+            let ip_key = ContractStorage::hash_ip(IP);
+            let ip_blocked = events_of::<IpBlockedEvent>();
+            assert_eq!(ip_blocked.len(), 1);
+            assert_eq!((ip_blocked[0].user_ip.clone(), ip_blocked[0].admin), (ip_key.clone(), accounts().alice));
+            let ip_unblocked = events_of::<IpUnblockedEvent>();
+            assert_eq!(ip_unblocked.len(), 1);
+            assert_eq!(ip_unblocked[0].user_ip, ip_key);
+        }
+
+        #[ink::test]
+        fn claim_fee_is_kept_by_the_contract() {
+            let mut faucet = faucet(1_000);
+            faucet.set_claim_fee(10).unwrap();
+            set_caller(accounts().bob);
+            let bob_before = balance_of(accounts().bob);
+            assert_eq!(faucet.get_coin(IP.to_vec()).map(|receipt| receipt.amount), Ok(90));
+            assert_eq!(balance_of(accounts().bob), bob_before + 90);
+            assert_eq!(balance_of(contract_id()), 910);
+            set_caller(accounts().charlie);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            assert_eq!(faucet.get_stats_and_settings().total_fees_collected, 20);
+
+            // with bump on, a net payout under min_transfer is raised to it
+            set_caller(accounts().alice);
+            faucet.set_min_transfer(95, true).unwrap();
+            set_caller(accounts().django);
+            assert_eq!(faucet.get_coin(IP.to_vec()).map(|receipt| receipt.amount), Ok(95));
+            assert_eq!(faucet.get_stats_and_settings().total_fees_collected, 30);
+        }
+
+        #[ink::test]
+        fn fees_must_leave_something_of_each_payout() {
+            let mut faucet = faucet(1_000);
+            assert_eq!(faucet.set_claim_fee(100), Err(Error::FeeTooHigh));
+            faucet.set_claim_fee(99).unwrap();
+            assert_eq!(faucet.set_get_payout(99), Err(Error::FeeTooHigh));
+            assert_eq!(faucet.set_payouts_and_fund(100, 99, 0, 1000, 5, 0, 0), Err(Error::FeeTooHigh));
+            assert_eq!(faucet.set_payouts_and_fund(100, 100, 50, 1000, 5, 0, 0), Err(Error::FeeTooHigh));
+            assert_eq!(faucet.set_pool(1, 99, 1000, 0), Err(Error::FeeTooHigh));
+            faucet.set_pool(1, 100, 1000, 0).unwrap();
+            assert_eq!(faucet.get_payout_amount(), 100);
+            set_caller(accounts().bob);
+            assert_eq!(faucet.set_claim_fee(1), Err(Error::PermissionDenied));
+        }
+
+        #[ink::test]
+        fn reconcile_counts_direct_transfers_once() {
+            let mut faucet = faucet(1_000);
+            test::set_value_transferred::<Env>(1_000);
+            faucet.fund().unwrap();
+            test::set_value_transferred::<Env>(0);
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            assert_eq!(faucet.reconcile(), Err(Error::PermissionDenied));
+
+            set_caller(accounts().alice);
+            assert_eq!(faucet.reconcile(), Ok(0));
+
+            // coin sent straight to the contract address
+            set_contract_balance(1_400);
+            assert_eq!(faucet.reconcile(), Ok(500));
+            assert_eq!(faucet.total_funded(), 1_500);
+            let event = events_of::<ReconcileEvent>().pop().unwrap();
+            assert_eq!((event.adjustment, event.total_funded), (500, 1_500));
+
+            // a queued claim is in both the balance and total_payouts, it is counted once
+            faucet.set_pending_timeout_ms(10_000).unwrap();
+            let (keypair, beneficiary) = signer(&mut faucet, 12);
+            let signature = sign_claim(&keypair, contract_id(), b"queue_claim", beneficiary, IP, 0);
+            faucet.queue_claim(beneficiary, IP.to_vec(), signature, 0).unwrap();
+            assert_eq!(faucet.reconcile(), Ok(0));
+            faucet.finalize_claim(beneficiary).unwrap();
+            assert_eq!(faucet.reconcile(), Ok(0));
+            assert_eq!(faucet.total_funded(), 1_500);
+        }
+
+        #[ink::test]
+        fn region_totals_are_kept_per_region() {
+            let mut faucet = faucet(1_000);
+            set_caller(accounts().bob);
+            assert_eq!(
+                faucet.get_coin_with_region(IP.to_vec(), vec![b'r'; MAX_REGION_LEN + 1]),
+                Err(Error::RegionTooLong)
+            );
+            faucet.get_coin_with_region(IP.to_vec(), b"EU".to_vec()).unwrap();
+            set_caller(accounts().charlie);
+            faucet.get_coin_with_region(IP.to_vec(), b"EU".to_vec()).unwrap();
+            set_caller(accounts().django);
+            faucet.get_coin_with_region(IP.to_vec(), b"NA".to_vec()).unwrap();
+            set_caller(accounts().eve);
+            faucet.get_coin_with_region(IP.to_vec(), Vec::new()).unwrap();
+
+            assert_eq!(faucet.region_total(b"EU".to_vec()), 200);
+            assert_eq!(faucet.region_total(b"NA".to_vec()), 100);
+            assert_eq!(faucet.region_total(Vec::new()), 0);
+        }
+
+        #[ink::test]
+        fn account_counter_follows_adds_and_removals() {
+            let mut faucet = faucet(1_000);
+            set_now(1_000);
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            set_caller(accounts().charlie);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            // a repeat claim is not a new account
+            set_now(2_000);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            assert_eq!(faucet.get_stats_and_settings().total_pebble_accounts, 2);
+            assert_eq!(faucet.account_count(), 2);
+
+            set_now(10_000);
+            set_caller(accounts().alice);
+            assert_eq!(faucet.prune_stale(vec![accounts().bob, accounts().bob], 5_000), Ok(1));
+            assert_eq!(faucet.get_stats_and_settings().total_pebble_accounts, 1);
+            assert_eq!(faucet.account_count(), 1);
+
+            // removing an account that is not in the index changes neither count
+            faucet.decrement_accounts(accounts().eve);
+            assert_eq!(faucet.get_stats_and_settings().total_pebble_accounts, 1);
+            assert_eq!(faucet.account_count(), 1);
+
+            // a pruned account that comes back is counted again
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            assert_eq!(faucet.get_stats_and_settings().total_pebble_accounts, 2);
+            assert_eq!(faucet.account_count(), 2);
+        }
+
+        #[ink::test]
+        fn ip_usage_shows_count_and_limit() {
+            let mut faucet = faucet(1_000);
+            assert_eq!(faucet.ip_usage(IP.to_vec()), (0, 5));
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            set_caller(accounts().charlie);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            assert_eq!(faucet.ip_usage(IP.to_vec()), (2, 5));
+            assert_eq!(faucet.ip_usage(b"10.0.0.2".to_vec()), (0, 5));
+        }
+
+        #[ink::test]
+        fn saturated_total_sets_the_overflow_flag() {
+            let mut faucet = faucet(1_000);
+            faucet.total_payouts = Balance::MAX - 50;
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            let stats = faucet.get_stats_and_settings();
+            assert!(stats.stats_overflowed);
+            assert_eq!(stats.total_payouts, Balance::MAX);
+
+            set_caller(accounts().alice);
+            faucet.reset_stats().unwrap();
+            assert!(!faucet.get_stats_and_settings().stats_overflowed);
+        }
+
+        #[ink::test]
+        fn low_balance_pauses_the_faucet() {
+            let mut faucet = faucet(1_000);
+            faucet.set_auto_pause_threshold(850).unwrap();
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            assert!(!faucet.get_stats_and_settings().paused);
+            set_caller(accounts().charlie);
+            faucet.get_coin(IP.to_vec()).unwrap();
+            assert!(faucet.get_stats_and_settings().paused);
+            let event = events_of::<AutoPausedEvent>().pop().unwrap();
+            assert_eq!((event.balance, event.threshold), (800, 850));
+
+            set_caller(accounts().django);
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::Paused));
+
+            // root can unpause after refunding
+            set_caller(accounts().alice);
+            set_contract_balance(2_000);
+            faucet.set_paused(false).unwrap();
+            set_caller(accounts().django);
+            assert!(faucet.get_coin(IP.to_vec()).is_ok());
+        }
+
+        #[ink::test]
+        fn receipt_gives_the_next_eligible_time() {
+            let mut faucet = faucet(1_000);
+            set_now(5_000);
+            set_caller(accounts().bob);
+            assert_eq!(
+                faucet.get_coin(IP.to_vec()),
+                Ok(ClaimReceipt { amount: 100, next_eligible: 6_000 })
+            );
+            set_now(5_999);
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::RateLimited));
+            set_now(6_000);
+            assert!(faucet.get_coin(IP.to_vec()).is_ok());
+        }
+
+        #[ink::test]
+        fn pools_keep_their_own_claims_and_limits() {
+            let mut faucet = faucet(1_000);
+            faucet.set_pool(1, 50, 5_000, 0).unwrap();
+            assert_eq!(faucet.set_pool(0, 50, 5_000, 0), Err(Error::InvalidPool));
+            faucet.set_limit_ip_total(1).unwrap();
+            set_now(5_000);
+            set_caller(accounts().bob);
+            faucet.get_coin(IP.to_vec()).unwrap();
+
+            // a claim from the main faucet does not start the pool's timer
+            assert_eq!(faucet.get_coin_from_pool(1, IP.to_vec()), Ok(50));
+            let event = events_of::<PoolClaimEvent>().pop().unwrap();
+            assert_eq!((event.pool_id, event.pebble, event.payout), (1, accounts().bob, 50));
+
+            // each pool has its own timer
+            set_now(6_000);
+            assert!(faucet.get_coin(IP.to_vec()).is_ok());
+            assert_eq!(faucet.get_coin_from_pool(1, IP.to_vec()), Err(Error::RateLimited));
+            set_now(10_000);
+            assert!(faucet.get_coin_from_pool(1, IP.to_vec()).is_ok());
+
+            // and its own IP address tags
+            set_caller(accounts().charlie);
+            assert_eq!(faucet.get_coin(IP.to_vec()), Err(Error::IpLimitReached));
+            assert_eq!(faucet.get_coin_from_pool(1, b"10.0.0.2".to_vec()), Ok(50));
+            assert_eq!(faucet.get_user_details(accounts().charlie), None);
+
+            // a pool only user is not one of the main faucet's accounts
+            assert_eq!(faucet.get_stats_and_settings().total_pebble_accounts, 1);
+            assert_eq!(faucet.account_count(), 1);
+            assert_eq!(faucet.list_accounts(0, 10), vec![accounts().bob]);
+
+            assert_eq!(faucet.get_coin_from_pool(0, IP.to_vec()), Err(Error::InvalidPool));
+            assert_eq!(faucet.get_coin_from_pool(7, IP.to_vec()), Err(Error::InvalidPool));
+            let pool = faucet.get_pool(1).unwrap();
+            assert_eq!((pool.paid_out, pool.claims), (150, 3));
+        }
+
+        #[ink::test]
+        fn pool_budget_caps_its_payouts() {
+            let mut faucet = faucet(1_000);
+            faucet.set_pool(1, 50, 5_000, 120).unwrap();
+            set_caller(accounts().bob);
+            faucet.get_coin_from_pool(1, IP.to_vec()).unwrap();
+            set_caller(accounts().charlie);
+            faucet.get_coin_from_pool(1, IP.to_vec()).unwrap();
+            set_caller(accounts().django);
+            assert_eq!(faucet.get_coin_from_pool(1, IP.to_vec()), Err(Error::PoolBudgetReached));
+            // the main faucet is not limited by the pool's budget
+            assert!(faucet.get_coin(IP.to_vec()).is_ok());
+            assert_eq!(faucet.get_pool(1).unwrap().paid_out, 100);
+        }
+
+        #[ink::test]
+        fn ineligible_callers_get_ok_false() {
+            let mut faucet = faucet(50);
+            set_caller(accounts().bob);
+            assert_eq!(faucet.check_eligibility(IP.to_vec()), Ok(false));
+            set_contract_balance(1_000);
+            assert_eq!(faucet.check_eligibility(IP.to_vec()), Ok(true));
+            assert_eq!(faucet.check_eligibility(Vec::new()), Ok(false));
+
+            set_caller(accounts().alice);
+            faucet.set_paused(true).unwrap();
+            set_caller(accounts().bob);
+            assert_eq!(faucet.check_eligibility(IP.to_vec()), Ok(false));
+        }
+
+        #[ink::test]
+        fn only_root_withdraws_and_it_is_logged() {
+            let mut faucet = faucet(1_000);
+            set_now(3_000);
+            set_caller(accounts().bob);
+            assert_eq!(faucet.withdraw_funds(100, accounts().bob), Err(Error::PermissionDenied));
+
+            set_caller(accounts().alice);
+            assert_eq!(faucet.withdraw_funds(1_001, accounts().charlie), Err(Error::InsufficientBalance));
+            let charlie_before = balance_of(accounts().charlie);
+            faucet.withdraw_funds(400, accounts().charlie).unwrap();
+            assert_eq!(balance_of(accounts().charlie), charlie_before + 400);
+            let event = events_of::<WithdrawEvent>().pop().unwrap();
+            assert_eq!((event.timestamp, event.to, event.amount), (3_000, accounts().charlie, 400));
+        }
+
+        #[ink::test]
+        fn batch_payout_caps_the_recipient_list() {
+            let mut faucet = faucet(1_000);
+            let too_many = vec![accounts().bob; MAX_BATCH_RECIPIENTS + 1];
+            assert_eq!(faucet.batch_payout(too_many, 1), Err(Error::TooManyRecipients));
+            assert_eq!(faucet.batch_payout(vec![accounts().bob; MAX_BATCH_RECIPIENTS], 1), Ok(50));
+            set_caller(accounts().bob);
+            assert_eq!(faucet.batch_payout(vec![accounts().bob], 1), Err(Error::PermissionDenied));
+        }
+
+        #[ink::test]
+        fn relayed_claims_need_a_registered_signer() {
+            let mut faucet = faucet(1_000);
+            let (keypair, beneficiary) = signer(&mut faucet, 13);
+            let unregistered = AccountId::from([14; 32]);
+            let signature = sign_claim(&keypair, contract_id(), b"claim_for", unregistered, IP, 0);
+            set_caller(accounts().bob);
+            assert_eq!(
+                faucet.claim_for(unregistered, IP.to_vec(), signature, 0),
+                Err(Error::SignerNotRegistered)
+            );
+            assert_eq!(faucet.get_signer(unregistered), None);
+
+            // registering a new key replaces the old one
+            let (other_key, _) = signer(&mut faucet, 15);
+            let other_public = secp256k1::PublicKey::from_secret_key(secp256k1::SECP256K1, &other_key);
+            set_caller(beneficiary);
+            faucet.register_signer(other_public.serialize()).unwrap();
+            assert_eq!(faucet.get_signer(beneficiary), Some(other_public.serialize()));
+            set_caller(accounts().bob);
+            let signature = sign_claim(&keypair, contract_id(), b"claim_for", beneficiary, IP, 0);
+            assert_eq!(faucet.claim_for(beneficiary, IP.to_vec(), signature, 0), Err(Error::BadSignature));
+            let signature = sign_claim(&other_key, contract_id(), b"claim_for", beneficiary, IP, 0);
+            assert_eq!(faucet.claim_for(beneficiary, IP.to_vec(), signature, 0), Ok(100));
+        }
+
+        // END OF UNIT TESTS
+    }
 
 }